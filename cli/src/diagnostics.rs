@@ -0,0 +1,489 @@
+use std::{
+    env,
+    fs::read_to_string,
+    io::{self, IsTerminal},
+    path::Path,
+};
+
+use interpreter::error::{RuntimeError, RuntimeErrorKind};
+use lexer::{
+    errors::{LexicalError, LexicalErrorKind},
+    utils::Position,
+};
+use nu_ansi_term::{Color, Style};
+use parser::error::{ParsingError, ParsingErrorKind};
+
+/// Source lines shown at most for a label spanning multiple lines.
+const MAX_LABEL_LINES: u32 = 4;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Label {
+    pub position: Position,
+    pub message: String,
+    /// The primary label points at the cause of the error, the others give
+    /// some context
+    pub primary: bool,
+}
+
+/// Error report rendered with the source lines it points at.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub title: String,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    /// Calls active when the error was raised, the most recent one first
+    pub traceback: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(title: &str, message: impl Into<String>) -> Self {
+        Self {
+            title: title.to_owned(),
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+            traceback: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, position: Position, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            position,
+            message: message.into(),
+            primary: self.labels.is_empty(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_traceback(mut self, traceback: Vec<String>) -> Self {
+        self.traceback = traceback;
+        self
+    }
+
+    /// Renders the diagnostic, `name` is the name of the source shown next to
+    /// the location of the primary label.
+    pub fn render(&self, name: &str, source: &str, color: bool) -> String {
+        let paint = |style: Style, text: &str| match color {
+            true => style.paint(text).to_string(),
+            false => text.to_owned(),
+        };
+        let error_style = Color::Red.bold();
+        let gutter_style = Color::LightBlue.bold();
+
+        let mut output = format!(
+            "{}: {}\n",
+            paint(error_style, &self.title),
+            paint(Style::new().bold(), &self.message)
+        );
+
+        let lines: Vec<&str> = source
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        let shown_lines = self.get_shown_lines(lines.len() as u32);
+        let width = shown_lines
+            .last()
+            .map_or(1, |line| (line + 1).to_string().len());
+        let gutter = |number: &str| paint(gutter_style, &format!("{number:>width$} |"));
+
+        if let Some(label) = self.labels.first() {
+            let position = label.position;
+            output += &format!(
+                "{}{} {}:{}:{}\n",
+                " ".repeat(width),
+                paint(gutter_style, "-->"),
+                name,
+                position.line_start + 1,
+                position.col_start + 1
+            );
+        }
+
+        if !shown_lines.is_empty() {
+            output += &format!("{}\n", gutter(""));
+        }
+
+        let mut previous = None;
+
+        for &line in &shown_lines {
+            if previous.is_some_and(|previous| line > previous + 1) {
+                output += &format!("{}\n", paint(gutter_style, &format!("{:>width$}", "...")));
+            }
+            previous = Some(line);
+
+            let text = lines[line as usize];
+            output += &format!(
+                "{} {}\n",
+                gutter(&(line + 1).to_string()),
+                expand_tabs(text)
+            );
+
+            for label in &self.labels {
+                let position = label.position;
+
+                if line < position.line_start || line > position.line_end {
+                    continue;
+                }
+
+                let start = match line == position.line_start {
+                    true => position.col_start as usize,
+                    false => 0,
+                };
+                let end = match line == position.line_end {
+                    true => position.col_end as usize,
+                    false => text.chars().count().saturating_sub(1),
+                };
+                let (marker, style) = match label.primary {
+                    true => ('^', error_style),
+                    false => ('-', gutter_style),
+                };
+                let prefix: String = text.chars().take(start).collect();
+                let underline = marker.to_string().repeat(end.max(start) - start + 1);
+                let message = match line == position.line_end && !label.message.is_empty() {
+                    true => format!(" {}", label.message),
+                    false => String::new(),
+                };
+
+                output += &format!(
+                    "{} {}{}\n",
+                    gutter(""),
+                    " ".repeat(display_width(&prefix)),
+                    paint(style, &(underline + &message))
+                );
+            }
+        }
+
+        if !shown_lines.is_empty() && (!self.notes.is_empty() || self.help.is_some()) {
+            output += &format!("{}\n", gutter(""));
+        }
+
+        let padding = " ".repeat(width + 1);
+
+        for note in &self.notes {
+            output += &format!(
+                "{padding}= {}: {note}\n",
+                paint(Style::new().bold(), "note")
+            );
+        }
+
+        if let Some(help) = &self.help {
+            output += &format!(
+                "{padding}= {}: {help}\n",
+                paint(Style::new().bold(), "help")
+            );
+        }
+
+        if !self.traceback.is_empty() {
+            output += &format!(
+                "{}\n",
+                paint(Style::new().bold(), "Traceback (most recent call first):")
+            );
+
+            for call in &self.traceback {
+                output += &format!("  {call}\n");
+            }
+        }
+
+        output
+    }
+
+    /// The lines covered by the labels, long labels only show their first and
+    /// last lines.
+    fn get_shown_lines(&self, line_count: u32) -> Vec<u32> {
+        let mut lines: Vec<u32> = Vec::new();
+
+        for label in &self.labels {
+            let position = label.position;
+            let end = position.line_end.max(position.line_start);
+
+            for line in position.line_start..=end {
+                let is_shown = end - position.line_start < MAX_LABEL_LINES
+                    || line < position.line_start + MAX_LABEL_LINES / 2
+                    || line > end - MAX_LABEL_LINES / 2;
+
+                if is_shown && line < line_count {
+                    lines.push(line);
+                }
+            }
+        }
+
+        lines.sort();
+        lines.dedup();
+
+        lines
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', "    ")
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(|ch| if ch == '\t' { 4 } else { 1 }).sum()
+}
+
+/// Prints the diagnostic to the standard error, colours are used when it is a
+/// terminal and `NO_COLOR` is not set.
+pub fn print_diagnostic(diagnostic: &Diagnostic, name: &str, source: &str) {
+    eprint!("{}", diagnostic.render(name, source, use_color()));
+}
+
+/// Prints a runtime error of the code named `name`, an error raised in an
+/// imported module is shown against the source of that module.
+pub fn print_runtime_error(error: &RuntimeError, name: &str, source: &str) {
+    eprint!("{}", render_runtime_error(error, name, source, use_color()));
+}
+
+pub fn render_runtime_error(error: &RuntimeError, name: &str, source: &str, color: bool) -> String {
+    let diagnostic = Diagnostic::from(error);
+    let module = error
+        .path
+        .as_deref()
+        .filter(|path| *path != Path::new(name))
+        .and_then(|path| Some((path.display().to_string(), read_to_string(path).ok()?)));
+
+    match module {
+        Some((name, source)) => diagnostic.render(&name, &source, color),
+        None => diagnostic.render(name, source, color),
+    }
+}
+
+fn use_color() -> bool {
+    io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+impl From<&LexicalError> for Diagnostic {
+    fn from(error: &LexicalError) -> Self {
+        lexical_error(&error.kind, error.position)
+    }
+}
+
+impl From<&ParsingError> for Diagnostic {
+    fn from(error: &ParsingError) -> Self {
+        parsing_error(&error.kind, error.position)
+    }
+}
+
+fn lexical_error(kind: &LexicalErrorKind, position: Position) -> Diagnostic {
+    let diagnostic = Diagnostic::new("Lexical error", kind.to_string());
+
+    match kind {
+        LexicalErrorKind::UnexpectedCharacter(_) => {
+            diagnostic.with_label(position, "unexpected character")
+        }
+        LexicalErrorKind::TrailingQuote(quote) => diagnostic
+            .with_label(position, "string is never closed")
+            .with_help(format!(
+                "add a closing {quote} or use triple quotes for a multi-line string"
+            )),
+        LexicalErrorKind::UnterminatedString => diagnostic
+            .with_label(position, "string is never closed")
+            .with_help("add the closing triple quotes"),
+        LexicalErrorKind::InvalidEscapeChar(_) => {
+            diagnostic.with_label(position, "invalid escape").with_note(
+                "the valid escapes are \\n \\t \\r \\0 \\' \\\" \\\\ \\{ \\} \\xHH and \\u{...}",
+            )
+        }
+        LexicalErrorKind::InvalidUnicodeEscape(_) => diagnostic
+            .with_label(position, "invalid escape")
+            .with_help("a unicode escape holds 1 to 6 hexadecimal digits, e.g. \\u{1F976}"),
+        LexicalErrorKind::UnterminatedComment => diagnostic
+            .with_label(position, "comment starts here")
+            .with_help("close the comment with '-->'"),
+        LexicalErrorKind::UnterminatedInterpolation => diagnostic
+            .with_label(position, "interpolation starts here")
+            .with_help("use '\\{' to write a literal brace"),
+        LexicalErrorKind::EmptyInterpolation => diagnostic
+            .with_label(position, "empty interpolation")
+            .with_help("use '\\{' and '\\}' to write literal braces"),
+        _ => diagnostic.with_label(position, ""),
+    }
+}
+
+fn parsing_error(kind: &ParsingErrorKind, position: Position) -> Diagnostic {
+    let diagnostic = Diagnostic::new("Syntax error", kind.to_string());
+
+    match kind {
+        ParsingErrorKind::UnexpedtedEndOfInput => {
+            diagnostic.with_label(position, "input ends here")
+        }
+        ParsingErrorKind::MissingClosingParenthesis => {
+            diagnostic.with_label(position, "expected ')'")
+        }
+        ParsingErrorKind::MissingClosingBracket => diagnostic.with_label(position, "expected ']'"),
+        ParsingErrorKind::MissingClosingBrace => diagnostic.with_label(position, "expected '}'"),
+        ParsingErrorKind::MissingAssignment => diagnostic
+            .with_label(position, "expected '='")
+            .with_help("variables must be initialized, e.g. `set name = null`"),
+        ParsingErrorKind::InvalidAssignment => diagnostic
+            .with_label(position, "cannot be assigned")
+            .with_note("only variables, indexes and properties can be assigned"),
+        ParsingErrorKind::ExpectedDeclaration(_) => diagnostic
+            .with_label(position, "expected 'set' or 'function'")
+            .with_note("only variable and function declarations can be exported"),
+        ParsingErrorKind::OutsideLoop(_) => diagnostic
+            .with_label(position, "not inside a loop")
+            .with_note("loops don't extend into the functions declared inside them"),
+        ParsingErrorKind::UndeclaredLabel(_) => diagnostic
+            .with_label(position, "no enclosing loop has this label")
+            .with_help("label a loop with `name: loop { ... }`"),
+        ParsingErrorKind::LexicalError(kind) => lexical_error(kind, position),
+        _ => diagnostic.with_label(position, ""),
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        let traceback = error.frames.iter().map(|frame| frame.to_string()).collect();
+
+        runtime_error(error).with_traceback(traceback)
+    }
+}
+
+fn runtime_error(error: &RuntimeError) -> Diagnostic {
+    let diagnostic = Diagnostic::new("Runtime error", error.kind.to_string());
+    let position = error.position;
+
+    match &error.kind {
+        RuntimeErrorKind::UndefinedIdentifier(name) => diagnostic
+            .with_label(position, "not found in this scope")
+            .with_help(format!("declare it first with `set {name} = ...`")),
+        RuntimeErrorKind::RedeclaringIdentifier(name) => diagnostic
+            .with_label(position, "already declared in this scope")
+            .with_help(format!("assign the existing variable with `{name} = ...`")),
+        RuntimeErrorKind::DivisionByZero => diagnostic.with_label(position, "division by zero"),
+        RuntimeErrorKind::NotFunciton => diagnostic.with_label(position, "not a function"),
+        RuntimeErrorKind::InvalidArgument(_, got) => {
+            diagnostic.with_label(position, format!("called with {got} argument(s)"))
+        }
+        RuntimeErrorKind::Throw(_) => diagnostic
+            .with_label(position, "thrown here")
+            .with_help("wrap the code in a `try` block to catch the exception"),
+        RuntimeErrorKind::CyclicImport(_) => diagnostic
+            .with_label(position, "imported here")
+            .with_help("move the shared code to a separate module"),
+        RuntimeErrorKind::UndefinedExport(name, _) => diagnostic
+            .with_label(position, "not exported")
+            .with_help(format!(
+                "export it from the module with `export set {name} = ...`"
+            )),
+        RuntimeErrorKind::LexicalError(kind) => Diagnostic {
+            title: "Runtime error".to_owned(),
+            ..lexical_error(kind, position)
+        },
+        RuntimeErrorKind::ParsingError(kind) => Diagnostic {
+            title: "Runtime error".to_owned(),
+            ..parsing_error(kind, position)
+        },
+        _ => diagnostic.with_label(position, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interpreter::{builtin::get_io_builtins, Interpreter};
+    use lexer::Lexer;
+    use parser::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_render() {
+        let source = "set a = 1\nset = 2\n";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let error = Parser::new(&tokens).parse().unwrap_err();
+        let output = Diagnostic::from(&error).render("main.ic", source, false);
+
+        assert_eq!(
+            output,
+            "Syntax error: expected identifer but got '='\n --> main.ic:2:5\n  |\n2 | set = 2\n  |     ^\n"
+        );
+
+        let diagnostic = Diagnostic::new("Runtime error", "invalid operation")
+            .with_label(Position::new(9, 4, 9, 8), "this value")
+            .with_label(Position::new(0, 0, 0, 2), "declared here")
+            .with_note("a note")
+            .with_help("some help");
+        let source = "set x = 1\n".repeat(9) + "\tprint(x)";
+
+        assert_eq!(
+            diagnostic.render("main.ic", &source, false),
+            [
+                "Runtime error: invalid operation",
+                "  --> main.ic:10:5",
+                "   |",
+                " 1 | set x = 1",
+                "   | --- declared here",
+                "...",
+                "10 |     print(x)",
+                "   |        ^^^^^ this value",
+                "   |",
+                "   = note: a note",
+                "   = help: some help",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let diagnostic = Diagnostic::new("Runtime error", "division by zero")
+            .with_traceback(vec!["in 'f' called at line 1, col 1".to_owned()]);
+
+        assert_eq!(
+            diagnostic.render("main.ic", "", false),
+            "Runtime error: division by zero\nTraceback (most recent call first):\n  in 'f' called at line 1, col 1\n"
+        );
+    }
+
+    #[test]
+    fn test_render_module_error() {
+        let dir = env::temp_dir().join("icelang_test_render_module");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("m.ic"), "export function f() {\n    1 / 0\n}").unwrap();
+
+        let source = "set { f } = import('m');\nf();";
+        let name = dir.join("main.ic");
+        let interpreter = Interpreter::new(dir.clone());
+        interpreter.set_file(&name);
+        interpreter.load_builtin(get_io_builtins());
+        let error = interpreter.run_source(source).unwrap_err();
+        let output = render_runtime_error(&error, &name.display().to_string(), source, false);
+        let module = dir.join("m.ic").canonicalize().unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[1], format!(" --> {}:2:7", module.display()));
+        assert_eq!(lines[3], "2 |     1 / 0");
+        assert_eq!(
+            lines[6],
+            format!("  in 'f' called at {}, line 2, col 2", name.display())
+        );
+    }
+
+    #[test]
+    fn test_render_multiline() {
+        let source = "'''\na\nb\nc\nd\ne";
+        let error = Lexer::new(source).tokenize().unwrap_err();
+        let output = Diagnostic::from(&error).render("main.ic", source, false);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[3], "1 | '''");
+        assert_eq!(lines[4], "  | ^^^");
+        assert_eq!(lines[7], "...");
+        assert_eq!(lines[8], "5 | d");
+        assert_eq!(lines[11], "  | ^ string is never closed");
+
+        let output = Diagnostic::from(&error).render("main.ic", source, true);
+        assert!(output.contains("\u{1b}["));
+    }
+}
@@ -1,4 +1,4 @@
-use lexer::utils::{is_alphabetic, is_alphanumeric, is_quote, is_standard_symbol};
+use lexer::utils::{is_alphabetic, is_alphanumeric, is_quote, is_standard_symbol, starts_with};
 use nu_ansi_term::{Color, Style};
 use reedline::{Highlighter, StyledText};
 
@@ -32,7 +32,31 @@ impl Highlighter for IceHighlighter {
         let mut buffer = vec![];
 
         while let Some(char) = tokens.next() {
-            if is_standard_symbol(char) {
+            if char == '<' && starts_with(&tokens, "--") {
+                let mut depth = 1;
+                let mut comment = String::from("<--");
+                tokens.nth(1);
+
+                while depth > 0 {
+                    let char = match tokens.next() {
+                        Some(char) => char,
+                        None => break,
+                    };
+                    comment.push(char);
+
+                    if char == '<' && starts_with(&tokens, "--") {
+                        comment.push_str("--");
+                        tokens.nth(1);
+                        depth += 1;
+                    } else if char == '-' && starts_with(&tokens, "->") {
+                        comment.push_str("->");
+                        tokens.nth(1);
+                        depth -= 1;
+                    }
+                }
+
+                buffer.push((self.comment, comment));
+            } else if is_standard_symbol(char) {
                 if char == '-' {
                     buffer.push((self.symbol, char.to_string()));
 
@@ -92,6 +116,9 @@ impl Highlighter for IceHighlighter {
                         | "function"
                         | "lambda"
                         | "return"
+                        | "try"
+                        | "catch"
+                        | "export"
                         | "self"
                 );
 
@@ -105,7 +132,22 @@ impl Highlighter for IceHighlighter {
 
                 buffer.push((style, current));
             } else if char.is_ascii_digit() {
-                buffer.push((self.number, char.to_string()));
+                let mut current = String::from(char);
+
+                while let Some(&char) = tokens.peek() {
+                    let is_exponent_sign = matches!(char, '+' | '-')
+                        && current.ends_with(['e', 'E'])
+                        && !current.starts_with("0x");
+
+                    if is_alphanumeric(char) || char == '.' || is_exponent_sign {
+                        current.push(char);
+                        tokens.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                buffer.push((self.number, current));
             } else {
                 buffer.push((Style::new(), char.to_string()))
             }
@@ -2,7 +2,7 @@ use std::{fmt::Display, path::PathBuf};
 
 use interpreter::{
     builtin::{get_io_builtins, get_std_builtins},
-    value::{Range, Value},
+    value::{visit_container, Range, Value},
     Interpreter,
 };
 use lexer::Lexer;
@@ -10,8 +10,14 @@ use nu_ansi_term::{AnsiGenericString, Color};
 use parser::Parser;
 use reedline::{Reedline, Signal};
 
-use self::{highlighter::IceHighlighter, prompt::IcePrompt, validator::IceValidator};
+use self::{
+    diagnostics::{print_diagnostic, print_runtime_error, Diagnostic},
+    highlighter::IceHighlighter,
+    prompt::IcePrompt,
+    validator::IceValidator,
+};
 
+pub mod diagnostics;
 mod highlighter;
 mod prompt;
 mod validator;
@@ -31,45 +37,59 @@ pub fn color_value<'a>(value: &Value) -> AnsiGenericString<'a, str> {
 
             Color::LightRed.paint(s)
         }
+        Value::Integer(value) => Color::LightRed.paint(value.to_string()),
+        Value::BigInt(value) => Color::LightRed.paint(value.to_string()),
+        Value::Decimal(value) => Color::LightRed.paint(value.to_string()),
         Value::String(value) => Color::LightGreen.paint(format!("\"{value}\"")),
         Value::Boolean(value) => Color::Cyan.paint(format!("{:?}", value)),
         Value::Null => Color::DarkGray.paint("null"),
         Value::Array(items) => {
-            let mut s = String::new();
-            let mut iter = items.iter();
-            if let Some(item) = iter.next() {
-                s.push_str(&format!("{}", color_value(&item.borrow())));
-                for item in iter {
-                    s.push_str(&format!(", {}", color_value(&item.borrow())));
+            let s = visit_container(value, || {
+                let mut s = String::new();
+                let items = items.borrow();
+                let mut iter = items.iter();
+                if let Some(item) = iter.next() {
+                    s.push_str(&format!("{}", color_value(&item.borrow())));
+                    for item in iter {
+                        s.push_str(&format!(", {}", color_value(&item.borrow())));
+                    }
                 }
+                s
+            });
+
+            match s {
+                Some(s) => Color::White.paint(format!("[{}]", s)),
+                None => Color::White.paint("[...]"),
             }
-            Color::White.paint(format!("[{}]", s))
         }
         Value::Function(function) => {
-            let name = match &function.declaration.token {
-                Some(token) => &token.lexeme,
-                None => "anonymous",
-            };
-            Color::LightBlue.paint(format!("[Function {}]", name))
+            Color::LightBlue.paint(format!("[Function {}]", function.get_name()))
         }
         Value::Builtin(builtin) => Color::LightBlue.paint(format!("[Function {}]", builtin.name)),
         Value::Object(object) => {
-            let mut s = String::new();
-            let mut iter = object.values.iter();
-            if let Some((key, value)) = iter.next() {
-                s.push_str(&format!("{}: {}", key, color_value(&value.borrow())));
-                for (key, value) in iter {
-                    s.push_str(&format!(", {}: {}", key, color_value(&value.borrow())));
+            let s = visit_container(value, || {
+                let mut s = String::new();
+                let object = object.borrow();
+                let mut iter = object.values.iter();
+                if let Some((key, value)) = iter.next() {
+                    s.push_str(&format!("{}: {}", key, color_value(&value.borrow())));
+                    for (key, value) in iter {
+                        s.push_str(&format!(", {}: {}", key, color_value(&value.borrow())));
+                    }
                 }
+                s
+            });
+
+            match s {
+                Some(s) => Color::White.paint(format!("{{ {} }}", s)),
+                None => Color::White.paint("{...}"),
             }
-            Color::White.paint(format!("{{ {} }}", s))
         }
         Value::Range(range) => {
             let (start, end) = match range {
-                Range::NumberRange(value) => (
-                    Value::Number(value.start as f64),
-                    Value::Number(value.end as f64),
-                ),
+                Range::NumberRange(value) => {
+                    (Value::Integer(value.start), Value::Integer(value.end))
+                }
                 Range::CharRange(value) => (
                     Value::String(value.start.to_string()),
                     Value::String(value.end.to_string()),
@@ -106,36 +126,34 @@ pub fn repl_mode() {
                 let tokens = match tokens {
                     Ok(value) => value,
                     Err(err) => {
-                        print_errror("Parsing error", err);
+                        print_diagnostic(&Diagnostic::from(&err), "<repl>", &buffer);
                         continue;
                     }
                 };
-                let nodes = Parser::new(&tokens).parse();
-                let nodes = match nodes {
-                    Ok(value) => value,
-                    Err(err) => {
-                        print_errror("Syntax error", err);
-                        continue;
+                let (nodes, errors) = Parser::new(&tokens).parse_with_recovery();
+
+                if !errors.is_empty() {
+                    for err in errors {
+                        print_diagnostic(&Diagnostic::from(&err), "<repl>", &buffer);
                     }
-                };
+                    continue;
+                }
 
                 if nodes.is_empty() {
                     println!("{}", Color::DarkGray.paint("null"));
                 }
 
-                for node in nodes {
-                    let value = interpreter.interpret(node);
-                    match value {
-                        Ok(value) => {
-                            if let Some(value) = value {
-                                println!("{}", color_value(&value));
-                            }
-                        }
-                        Err(err) => {
-                            print_errror("Runtime error", err);
+                let value = interpreter.interpret_program(&nodes);
+                match value {
+                    Ok(value) => {
+                        if let Some(value) = value {
+                            println!("{}", color_value(&value));
                         }
-                    };
-                }
+                    }
+                    Err(err) => {
+                        print_runtime_error(&err, "<repl>", &buffer);
+                    }
+                };
             }
             Ok(Signal::CtrlD) | Ok(Signal::CtrlC) => {
                 println!("Bye!");
@@ -1,4 +1,4 @@
-use lexer::Lexer;
+use lexer::{errors::LexicalErrorKind, Lexer};
 use parser::{error::ParsingErrorKind, Parser};
 use reedline::{ValidationResult, Validator};
 
@@ -15,8 +15,12 @@ impl Validator for IceValidator {
         let tokens = Lexer::new(line).tokenize();
         let tokens = match tokens {
             Ok(value) => value,
-            Err(_) => {
-                return ValidationResult::Complete;
+            Err(err) => {
+                return match err.kind {
+                    LexicalErrorKind::UnterminatedComment
+                    | LexicalErrorKind::UnterminatedString => ValidationResult::Incomplete,
+                    _ => ValidationResult::Complete,
+                };
             }
         };
         let nodes = Parser::new(&tokens).parse();
@@ -1,31 +1,132 @@
 use crate::{
-    environment::RefEnv,
     error::{RuntimeError, RuntimeErrorKind},
-    value::Value,
-    EvalExpr, Interpreter,
+    module::RefModules,
+    value::{float_to_integer, RefArray, Value},
 };
 
-use lexer::tokens::Token;
-use parser::ast::Expression;
-use std::{f64::INFINITY, fmt, fs::read_to_string, io};
+use lexer::utils::Position;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, MathematicalOps, RoundingStrategy};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    fmt,
+    fs::read_to_string,
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+pub type NativeFn = dyn Fn(&CallContext, &[Value]) -> Result<Value, RuntimeError>;
+
+/// Calls function values and runs modules on behalf of native functions, each
+/// backend runs its own kind of functions and the modules imported from its
+/// code.
+pub trait Caller {
+    fn call(
+        &self,
+        context: &CallContext,
+        function: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError>;
+
+    /// Runs the source of the module at `file_path` and returns its exports.
+    fn run_module(
+        &self,
+        context: &CallContext,
+        file_path: &Path,
+        source: &str,
+    ) -> Result<Value, RuntimeError>;
+}
+
+/// Information about the call site handed to native functions.
+pub struct CallContext<'a> {
+    pub position: Position,
+    path: PathBuf,
+    file: PathBuf,
+    modules: RefModules,
+    caller: &'a dyn Caller,
+}
+
+impl<'a> CallContext<'a> {
+    pub fn new(
+        position: Position,
+        path: PathBuf,
+        file: PathBuf,
+        modules: RefModules,
+        caller: &'a dyn Caller,
+    ) -> Self {
+        Self {
+            position,
+            path,
+            file,
+            modules,
+            caller,
+        }
+    }
+
+    /// Calls a function value such as a callback, from the call site.
+    pub fn call(&self, function: &Value, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        self.caller.call(self, function, arguments)
+    }
 
-type BuiltinFn = fn(&RefEnv, token: &Token, &[Expression]) -> Result<Value, RuntimeError>;
+    /// Runs an imported module with the backend making the call.
+    pub fn run_module(&self, file_path: &Path, source: &str) -> Result<Value, RuntimeError> {
+        self.caller.run_module(self, file_path, source)
+    }
+
+    /// Directory that relative imports are resolved from.
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// File of the module making the call.
+    pub fn get_file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn get_modules(&self) -> RefModules {
+        self.modules.clone()
+    }
+
+    /// Creates an error located at the call site.
+    pub fn error(&self, kind: RuntimeErrorKind) -> RuntimeError {
+        RuntimeError::new(kind, self.position)
+    }
+}
 
+/// Function implemented in Rust. Its arguments are evaluated before the call
+/// and it may capture any state of the host.
 #[derive(Clone)]
 pub struct Builtin {
-    pub name: &'static str,
+    pub name: String,
     pub args: usize,
-    pub function: BuiltinFn,
+    pub function: Rc<NativeFn>,
 }
 
 impl Builtin {
-    pub fn new(name: &'static str, args: usize, function: BuiltinFn) -> Self {
+    pub fn new<F>(name: &str, args: usize, function: F) -> Self
+    where
+        F: Fn(&CallContext, &[Value]) -> Result<Value, RuntimeError> + 'static,
+    {
         Self {
-            name,
+            name: name.to_owned(),
             args,
-            function,
+            function: Rc::new(function),
         }
     }
+
+    pub fn call(&self, context: &CallContext, arguments: &[Value]) -> Result<Value, RuntimeError> {
+        let expected = self.args;
+        let got = arguments.len();
+
+        if expected != f64::INFINITY as usize && got != expected {
+            return Err(context.error(RuntimeErrorKind::InvalidArgument(expected, got)));
+        }
+
+        (self.function)(context, arguments)
+    }
 }
 
 impl fmt::Debug for Builtin {
@@ -40,147 +141,531 @@ impl fmt::Debug for Builtin {
 
 impl PartialEq for Builtin {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name
-    }
-}
-
-impl PartialOrd for Builtin {
-    fn partial_cmp(&self, _other: &Self) -> Option<std::cmp::Ordering> {
-        Some(std::cmp::Ordering::Less)
+        Rc::ptr_eq(&self.function, &other.function)
     }
 }
 
 pub fn get_std_builtins() -> Vec<Builtin> {
     vec![
         Builtin::new("type_of", 1, type_of),
+        Builtin::new("is_number", 1, is_number),
+        Builtin::new("is_integer", 1, is_integer),
         Builtin::new("length", 1, length),
+        Builtin::new("clone", 1, clone),
+        Builtin::new("deep_clone", 1, deep_clone),
+        Builtin::new("push", 2, push),
+        Builtin::new("pop", 1, pop),
+        Builtin::new("insert", 3, insert),
+        Builtin::new("remove", 2, remove),
+        Builtin::new("slice", 3, slice),
+        Builtin::new("concat", 2, concat),
+        Builtin::new("index_of", 2, index_of),
+        Builtin::new("contains", 2, contains),
+        Builtin::new("reverse", 1, reverse),
+        Builtin::new("sort", 1, sort),
+        Builtin::new("sort_by", 2, sort_by),
         Builtin::new("sqrt", 1, sqrt),
         Builtin::new("pow", 2, pow),
         Builtin::new("floor", 1, floor),
         Builtin::new("round", 1, round),
         Builtin::new("ceil", 1, ceil),
         Builtin::new("parse_number", 1, parse_number),
+        Builtin::new("int", 1, int),
+        Builtin::new("float", 1, float),
+        Builtin::new("bigint", 1, bigint),
+        Builtin::new("decimal", 1, decimal),
+        Builtin::new("throw", 1, throw),
     ]
 }
 
 pub fn get_io_builtins() -> Vec<Builtin> {
     vec![
-        Builtin::new("print", INFINITY as usize, io_print),
+        Builtin::new("print", f64::INFINITY as usize, io_print),
         Builtin::new("readline", 0, io_readline),
         Builtin::new("import", 1, import),
-        Builtin::new("export", 1, export),
     ]
 }
 
-fn type_of(env: &RefEnv, _: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let value = &args[0];
-    let value = value.evaluate_expression(env)?;
-    let value_type = Value::String(value.get_type());
+fn type_of(_: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let value_type = Value::String(args[0].get_type());
 
     Ok(value_type)
 }
 
-fn length(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let value = arg.evaluate_expression(env)?;
+/// Whether the value is a number of any kind.
+fn is_number(_: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Boolean(args[0].is_number()))
+}
 
-    match value {
-        Value::String(string) => Ok(Value::Number(string.len() as f64)),
-        Value::Array(array) => Ok(Value::Number(array.len() as f64)),
-        Value::Object(object) => Ok(Value::Number(object.values.len() as f64)),
-        _ => Err(RuntimeError::new(RuntimeErrorKind::InvalidArg, token.pos)),
+/// Whether the value is an integer or a big integer, a float without
+/// fractional part isn't one.
+fn is_integer(_: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let integer = matches!(args[0], Value::Integer(_) | Value::BigInt(_));
+
+    Ok(Value::Boolean(integer))
+}
+
+fn length(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::String(string) => Ok(Value::Integer(string.chars().count() as i64)),
+        Value::Array(array) => Ok(Value::Integer(array.borrow().len() as i64)),
+        Value::Object(object) => Ok(Value::Integer(object.borrow().values.len() as i64)),
+        _ => Err(context.error(RuntimeErrorKind::InvalidArg)),
     }
 }
 
-fn sqrt(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let value = arg.evaluate_expression(env)?;
+/// Copies an array or an object, the copy holds the same items.
+fn clone(_: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(args[0].shallow_clone())
+}
+
+/// Copies an array or an object along with the arrays and objects it holds.
+fn deep_clone(_: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(args[0].deep_clone())
+}
 
+fn expect_array(context: &CallContext, value: &Value) -> Result<RefArray, RuntimeError> {
     match value {
-        Value::Number(value) => Ok(Value::Number(value.sqrt())),
-        _ => Err(RuntimeError::new(
-            RuntimeErrorKind::TypeExpection("number".to_owned(), value.get_type()),
-            token.pos,
-        )),
+        Value::Array(array) => Ok(array.clone()),
+        value => Err(context.error(RuntimeErrorKind::TypeExpection(
+            "array".to_owned(),
+            value.get_type(),
+        ))),
     }
 }
 
-fn pow(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let value = &args[0];
-    let exponent = &args[1];
-    let value = value.evaluate_expression(env)?;
-    let exponent = exponent.evaluate_expression(env)?;
+/// Checks that an index is an integer no greater than `len`.
+fn expect_index(context: &CallContext, value: &Value, len: usize) -> Result<usize, RuntimeError> {
+    match value.as_integer().map(usize::try_from) {
+        Some(Ok(index)) if index <= len => Ok(index),
+        _ => Err(context.error(RuntimeErrorKind::InvalidIndex)),
+    }
+}
 
-    match (&value, exponent) {
-        (Value::Number(value), Value::Number(exponent)) => Ok(Value::Number(value.powf(exponent))),
-        _ => Err(RuntimeError::new(
-            RuntimeErrorKind::TypeExpection("number".to_owned(), value.get_type()),
-            token.pos,
-        )),
+fn push(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let array = expect_array(context, &args[0])?;
+    array
+        .borrow_mut()
+        .push(Rc::new(RefCell::new(args[1].clone())));
+
+    Ok(Value::Null)
+}
+
+/// Removes the last item, an empty array gives `null`.
+fn pop(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let array = expect_array(context, &args[0])?;
+    let item = array.borrow_mut().pop();
+
+    match item {
+        Some(item) => Ok(item.borrow().clone()),
+        None => Ok(Value::Null),
     }
 }
 
-fn floor(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let value = arg.evaluate_expression(env)?;
+/// Inserts an item before `index`, the length of the array appends it.
+fn insert(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let array = expect_array(context, &args[0])?;
+    let index = expect_index(context, &args[1], array.borrow().len())?;
+    array
+        .borrow_mut()
+        .insert(index, Rc::new(RefCell::new(args[2].clone())));
 
-    match value {
-        Value::Number(value) => Ok(Value::Number(value.floor())),
-        _ => Err(RuntimeError::new(
-            RuntimeErrorKind::TypeExpection("number".to_owned(), value.get_type()),
-            token.pos,
-        )),
+    Ok(Value::Null)
+}
+
+/// Removes the item at `index` and returns it.
+fn remove(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let array = expect_array(context, &args[0])?;
+    let len = array.borrow().len();
+
+    match expect_index(context, &args[1], len)? {
+        index if index < len => {
+            let item = array.borrow_mut().remove(index);
+            let item = item.borrow().clone();
+            Ok(item)
+        }
+        _ => Err(context.error(RuntimeErrorKind::InvalidIndex)),
     }
 }
 
-fn round(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let value = arg.evaluate_expression(env)?;
+/// Copies the items from `start` up to but not including `end` in a new
+/// array, indexes past the end are clamped to the length.
+fn slice(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let array = expect_array(context, &args[0])?;
+    let items = array.borrow();
+    let start = expect_index(context, &args[1], usize::MAX)?.min(items.len());
+    let end = expect_index(context, &args[2], usize::MAX)?.min(items.len());
+    let items = items[start..end.max(start)]
+        .iter()
+        .map(|item| Rc::new(RefCell::new(item.borrow().clone())))
+        .collect();
+
+    Ok(Value::new_array(items))
+}
+
+/// Creates a new array with the items of both arrays.
+fn concat(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let first = expect_array(context, &args[0])?;
+    let second = expect_array(context, &args[1])?;
+    let items = first
+        .borrow()
+        .iter()
+        .chain(second.borrow().iter())
+        .map(|item| Rc::new(RefCell::new(item.borrow().clone())))
+        .collect();
+
+    Ok(Value::new_array(items))
+}
 
-    match value {
-        Value::Number(value) => Ok(Value::Number(value.round())),
-        _ => Err(RuntimeError::new(
-            RuntimeErrorKind::TypeExpection("number".to_owned(), value.get_type()),
-            token.pos,
-        )),
+/// Index of the first item equal to the value, or -1 when there is none.
+fn index_of(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let array = expect_array(context, &args[0])?;
+    let index = array
+        .borrow()
+        .iter()
+        .position(|item| *item.borrow() == args[1]);
+
+    match index {
+        Some(index) => Ok(Value::Integer(index as i64)),
+        None => Ok(Value::Integer(-1)),
     }
 }
 
-fn ceil(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let value = arg.evaluate_expression(env)?;
+fn contains(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let array = expect_array(context, &args[0])?;
+    let found = array.borrow().iter().any(|item| *item.borrow() == args[1]);
 
-    match value {
-        Value::Number(value) => Ok(Value::Number(value.ceil())),
-        _ => Err(RuntimeError::new(
-            RuntimeErrorKind::TypeExpection("number".to_owned(), value.get_type()),
-            token.pos,
-        )),
+    Ok(Value::Boolean(found))
+}
+
+fn reverse(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    expect_array(context, &args[0])?.borrow_mut().reverse();
+
+    Ok(Value::Null)
+}
+
+/// Stable merge sort with a comparison that can fail. Unlike the sorts of the
+/// standard library, an inconsistent comparison gives an unspecified order
+/// rather than a panic.
+fn merge_sort<F>(mut items: Vec<Value>, compare: &mut F) -> Result<Vec<Value>, RuntimeError>
+where
+    F: FnMut(&Value, &Value) -> Result<Ordering, RuntimeError>,
+{
+    if items.len() <= 1 {
+        return Ok(items);
     }
+
+    let right = items.split_off(items.len() / 2);
+    let mut left = merge_sort(items, compare)?.into_iter().peekable();
+    let mut right = merge_sort(right, compare)?.into_iter().peekable();
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        match compare(b, a)? {
+            Ordering::Less => merged.extend(right.next()),
+            _ => merged.extend(left.next()),
+        }
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+/// Sorts the array in place with `compare`. The items are copied out during
+/// the sort so that the comparison may read the array, but changing the array
+/// from the comparison is an error rather than being overwritten.
+fn sort_with<F>(context: &CallContext, value: &Value, mut compare: F) -> Result<Value, RuntimeError>
+where
+    F: FnMut(&Value, &Value) -> Result<Ordering, RuntimeError>,
+{
+    let array = expect_array(context, value)?;
+    let slots = array.borrow().clone();
+    let items: Vec<Value> = slots.iter().map(|item| item.borrow().clone()).collect();
+    let sorted = merge_sort(items.clone(), &mut compare)?;
+
+    let unchanged = {
+        let current = array.borrow();
+        current.len() == slots.len()
+            && current
+                .iter()
+                .zip(&slots)
+                .zip(&items)
+                .all(|((slot, old), item)| Rc::ptr_eq(slot, old) && *slot.borrow() == *item)
+    };
+    if !unchanged {
+        return Err(context.error(RuntimeErrorKind::InvalidOperation(
+            "array modified during sort".to_owned(),
+        )));
+    }
+
+    *array.borrow_mut() = sorted
+        .into_iter()
+        .map(|item| Rc::new(RefCell::new(item)))
+        .collect();
+
+    Ok(Value::Null)
+}
+
+/// Sorts numbers or strings in ascending order.
+fn sort(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    sort_with(context, &args[0], |a, b| {
+        if !a.is_comparable_with(b) {
+            let is_orderable =
+                |value: &Value| value.is_number() || matches!(value, Value::String(_));
+            let (expected, found) = match (a, b) {
+                (a, _) if !is_orderable(a) => ("number or string", a.get_type()),
+                (_, b) if !is_orderable(b) => ("number or string", b.get_type()),
+                (Value::String(_), b) => ("string", b.get_type()),
+                (_, b) => ("number", b.get_type()),
+            };
+
+            return Err(context.error(RuntimeErrorKind::TypeExpection(expected.to_owned(), found)));
+        }
+
+        Ok(a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    })
 }
 
-fn parse_number(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let value = arg.evaluate_expression(env)?;
+/// Sorts with a function returning a negative number when its first argument
+/// goes first, a positive number when it goes last and zero otherwise.
+fn sort_by(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    sort_with(context, &args[0], |a, b| {
+        let order = context.call(&args[1], vec![a.clone(), b.clone()])?;
+
+        if !order.is_number() {
+            return Err(context.error(RuntimeErrorKind::TypeExpection(
+                "number".to_owned(),
+                order.get_type(),
+            )));
+        }
+
+        Ok(order
+            .partial_cmp(&Value::Integer(0))
+            .unwrap_or(Ordering::Equal))
+    })
+}
 
+fn expect_number(context: &CallContext, value: &Value) -> Result<f64, RuntimeError> {
+    match value.as_float() {
+        Some(value) => Ok(value),
+        None => Err(context.error(RuntimeErrorKind::TypeExpection(
+            "number".to_owned(),
+            value.get_type(),
+        ))),
+    }
+}
+
+/// Rounds a number with `round`, or `strategy` for decimals. Integers are
+/// returned as is and the result is an integer when it fits.
+fn round_with(
+    context: &CallContext,
+    value: &Value,
+    round: fn(f64) -> f64,
+    strategy: RoundingStrategy,
+) -> Result<Value, RuntimeError> {
     match value {
-        Value::String(value) => match value.parse::<f64>() {
-            Ok(number) => Ok(Value::Number(number)),
-            Err(_) => Err(RuntimeError::new(
-                RuntimeErrorKind::InvalidNumber,
-                token.pos,
-            )),
-        },
-        _ => Err(RuntimeError::new(
-            RuntimeErrorKind::TypeExpection("string".to_owned(), value.get_type()),
-            token.pos,
-        )),
-    }
-}
-
-fn io_print(env: &RefEnv, _: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    for arg in args {
-        let value = arg.evaluate_expression(env)?;
+        Value::Integer(_) | Value::BigInt(_) => Ok(value.clone()),
+        Value::Decimal(value) => {
+            // every decimal without fractional part fits in 128 bits
+            let value = value.round_dp_with_strategy(0, strategy).to_i128().unwrap();
+
+            match i64::try_from(value) {
+                Ok(value) => Ok(Value::Integer(value)),
+                Err(_) => Ok(Value::BigInt(BigInt::from(value))),
+            }
+        }
+        value => {
+            let value = round(expect_number(context, value)?);
+
+            match float_to_integer(value) {
+                Some(value) => Ok(Value::Integer(value)),
+                None => Ok(Value::Number(value)),
+            }
+        }
+    }
+}
+
+fn sqrt(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let value = expect_number(context, &args[0])?;
+
+    Ok(Value::Number(value.sqrt()))
+}
+
+/// Integer and decimal powers stay exact when the exponent is an integer,
+/// otherwise the power is computed on floats.
+fn pow(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let exponent = match &args[1] {
+        Value::Integer(_) | Value::BigInt(_) => args[1].as_integer(),
+        _ => None,
+    };
+
+    let power = match (&args[0], exponent) {
+        (Value::Integer(value), Some(exponent)) => u32::try_from(exponent)
+            .ok()
+            .and_then(|exponent| value.checked_pow(exponent))
+            .map(Value::Integer),
+        (Value::BigInt(value), Some(exponent)) => u32::try_from(exponent)
+            .ok()
+            .map(|exponent| Value::BigInt(value.pow(exponent))),
+        (Value::Decimal(value), Some(exponent)) => value.checked_powi(exponent).map(Value::Decimal),
+        _ => None,
+    };
+
+    if let Some(power) = power {
+        return Ok(power);
+    }
+
+    let value = expect_number(context, &args[0])?;
+    let exponent = expect_number(context, &args[1])?;
+
+    Ok(Value::Number(value.powf(exponent)))
+}
+
+fn floor(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    round_with(
+        context,
+        &args[0],
+        f64::floor,
+        RoundingStrategy::ToNegativeInfinity,
+    )
+}
+
+fn round(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let strategy = RoundingStrategy::MidpointAwayFromZero;
+
+    round_with(context, &args[0], f64::round, strategy)
+}
+
+fn ceil(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    round_with(
+        context,
+        &args[0],
+        f64::ceil,
+        RoundingStrategy::ToPositiveInfinity,
+    )
+}
+
+/// Parses a decimal with or without an exponent, digits that the decimal
+/// can't hold are rejected rather than rounded.
+fn parse_decimal(value: &str) -> Option<Decimal> {
+    match value.contains(['e', 'E']) {
+        true => Decimal::from_scientific(value).ok(),
+        false => Decimal::from_str_exact(value).ok(),
+    }
+}
+
+/// Parses a number like a literal: an integer, a big integer when it doesn't
+/// fit in 64 bits, or a float when the string is not an integer, big integers
+/// and decimals can also be marked with the `n` and `m` suffixes.
+fn parse_number(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::String(value) => {
+            let number = if let Some(digits) = value.strip_suffix('n') {
+                digits.parse::<BigInt>().ok().map(Value::BigInt)
+            } else if let Some(digits) = value.strip_suffix('m') {
+                parse_decimal(digits).map(Value::Decimal)
+            } else if let Ok(number) = value.parse::<i64>() {
+                Some(Value::Integer(number))
+            } else if let Ok(number) = value.parse::<BigInt>() {
+                Some(Value::BigInt(number))
+            } else {
+                value.parse::<f64>().ok().map(Value::Number)
+            };
+
+            match number {
+                Some(number) => Ok(number),
+                None => Err(context.error(RuntimeErrorKind::InvalidNumber)),
+            }
+        }
+        value => Err(context.error(RuntimeErrorKind::TypeExpection(
+            "string".to_owned(),
+            value.get_type(),
+        ))),
+    }
+}
+
+/// Parses strings and checks that the argument of a conversion is a number.
+fn number_argument(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let value = match &args[0] {
+        Value::String(_) => parse_number(context, args)?,
+        value => value.clone(),
+    };
+
+    match value.is_number() {
+        true => Ok(value),
+        false => Err(context.error(RuntimeErrorKind::TypeExpection(
+            "number".to_owned(),
+            value.get_type(),
+        ))),
+    }
+}
+
+/// Converts a number or a string to an integer, numbers are truncated toward
+/// zero.
+fn int(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let number = match number_argument(context, args)? {
+        Value::Number(number) => float_to_integer(number.trunc()),
+        Value::Decimal(number) => number.trunc().to_i64(),
+        value => value.as_integer(),
+    };
+
+    match number {
+        Some(number) => Ok(Value::Integer(number)),
+        None => Err(context.error(RuntimeErrorKind::InvalidNumber)),
+    }
+}
+
+/// Converts a number or a string to a float.
+fn float(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let number = number_argument(context, args)?.as_float().unwrap();
+
+    Ok(Value::Number(number))
+}
+
+/// Converts a number or a string to a big integer, numbers are truncated
+/// toward zero.
+fn bigint(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    if let Value::String(value) = &args[0] {
+        if let Ok(number) = value.parse::<BigInt>() {
+            return Ok(Value::BigInt(number));
+        }
+    }
+
+    let number = match number_argument(context, args)? {
+        Value::Number(number) => BigInt::from_f64(number.trunc()),
+        Value::Decimal(number) => number.trunc().to_i128().map(BigInt::from),
+        value => value.as_bigint(),
+    };
+
+    match number {
+        Some(number) => Ok(Value::BigInt(number)),
+        None => Err(context.error(RuntimeErrorKind::InvalidNumber)),
+    }
+}
+
+/// Converts a number or a string to a decimal. Strings are read exactly while
+/// floats give the shortest decimal that reads back as them.
+fn decimal(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    if let Value::String(value) = &args[0] {
+        if let Some(number) = parse_decimal(value) {
+            return Ok(Value::Decimal(number));
+        }
+    }
+
+    match number_argument(context, args)?.as_decimal() {
+        Some(number) => Ok(Value::Decimal(number)),
+        None => Err(context.error(RuntimeErrorKind::InvalidNumber)),
+    }
+}
+
+fn throw(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(context.error(RuntimeErrorKind::Throw(args[0].clone())))
+}
+
+fn io_print(_: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    for value in args {
         print!("{value}");
     }
 
@@ -188,7 +673,7 @@ fn io_print(env: &RefEnv, _: &Token, args: &[Expression]) -> Result<Value, Runti
     Ok(Value::Null)
 }
 
-fn io_readline(_: &RefEnv, _: &Token, _: &[Expression]) -> Result<Value, RuntimeError> {
+fn io_readline(_: &CallContext, _: &[Value]) -> Result<Value, RuntimeError> {
     let mut input = String::new();
     io::stdin()
         .read_line(&mut input)
@@ -197,49 +682,42 @@ fn io_readline(_: &RefEnv, _: &Token, _: &[Expression]) -> Result<Value, Runtime
     Ok(Value::String(input.trim_end().to_string()))
 }
 
-fn import(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let mut value = arg.evaluate_expression(env)?;
-    let file_path = match &mut value {
-        Value::String(value) => {
-            if !value.ends_with(".ic") {
-                value.push_str(".ic");
-            }
-
-            value
-        }
-        _ => {
-            return Err(RuntimeError::new(
-                RuntimeErrorKind::InvalidPath(value),
-                token.pos,
-            ))
-        }
+fn import(context: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    let file_path = match &args[0] {
+        Value::String(value) if value.ends_with(".ic") => value.clone(),
+        Value::String(value) => format!("{value}.ic"),
+        value => return Err(context.error(RuntimeErrorKind::InvalidPath(value.clone()))),
     };
-    let file_path = &env.borrow().get_path().join(file_path);
-    let source = match read_to_string(file_path) {
-        Ok(value) => value,
-        Err(_) => {
-            return Err(RuntimeError::new(
-                RuntimeErrorKind::ModuleNotFound(format!("{:?}", file_path)),
-                token.pos,
-            ))
-        }
+    let file_path = &context.get_path().join(file_path);
+    let module_not_found =
+        || context.error(RuntimeErrorKind::ModuleNotFound(format!("{:?}", file_path)));
+    let file_path = file_path.canonicalize().map_err(|_| module_not_found())?;
+    let modules = context.get_modules();
+
+    if let Some(value) = modules.borrow().get(&file_path) {
+        return Ok(value);
+    }
+
+    if let Err(chain) = modules.borrow_mut().enter(file_path.clone()) {
+        let chain = chain
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        return Err(context.error(RuntimeErrorKind::CyclicImport(chain)));
+    }
+
+    let source = read_to_string(&file_path);
+    let result = match source {
+        Ok(source) => context.run_module(&file_path, &source),
+        Err(_) => Err(module_not_found()),
     };
-    let path = file_path.parent().unwrap().to_path_buf();
-    let interpreter = Interpreter::new(path);
-    interpreter.load_builtin(get_std_builtins());
-    interpreter.load_builtin(get_io_builtins());
-    let value = interpreter.run_source(&source)?;
 
-    Ok(value)
-}
+    let mut modules = modules.borrow_mut();
+    modules.leave();
 
-fn export(env: &RefEnv, token: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    let arg = &args[0];
-    let value = arg.evaluate_expression(env)?;
+    if let Ok(value) = &result {
+        modules.insert(file_path, value.clone());
+    }
 
-    Err(RuntimeError::new(
-        RuntimeErrorKind::Export(value),
-        token.pos,
-    ))
+    result
 }
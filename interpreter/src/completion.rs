@@ -0,0 +1,60 @@
+use crate::{error::RuntimeError, value::Value};
+
+/// How the evaluation of a statement ended.
+#[derive(Debug, PartialEq)]
+pub enum Completion {
+    /// Evaluation went on to the next statement, with the value of the
+    /// statement if it has one
+    Normal(Option<Value>),
+    /// `break`, with the label of the loop to exit
+    Break(Option<String>),
+    /// `continue`, with the label of the loop to continue
+    Continue(Option<String>),
+    Return(Value),
+}
+
+impl Completion {
+    /// The value a program or a function ends with.
+    pub fn into_value(self) -> Option<Value> {
+        match self {
+            Completion::Normal(value) => value,
+            Completion::Return(value) => Some(value),
+            Completion::Break(_) | Completion::Continue(_) => None,
+        }
+    }
+
+    /// Whether a `break` or `continue` with the label `target` applies to the
+    /// loop labelled `label`, unlabelled ones apply to the innermost loop.
+    pub fn targets(target: &Option<String>, label: Option<&str>) -> bool {
+        match target {
+            Some(target) => label == Some(target.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// Why an expression did not produce a value. Besides errors, the branches of
+/// an `if` or a `match` can end with a `break`, a `continue` or a `return`
+/// that must reach the enclosing statement.
+#[derive(Debug, PartialEq)]
+pub enum Interrupt {
+    Error(RuntimeError),
+    Completion(Completion),
+}
+
+impl Interrupt {
+    /// Turns the interruption into the completion of the statement evaluating
+    /// the expression.
+    pub fn into_completion(self) -> Result<Completion, RuntimeError> {
+        match self {
+            Interrupt::Error(error) => Err(error),
+            Interrupt::Completion(completion) => Ok(completion),
+        }
+    }
+}
+
+impl From<RuntimeError> for Interrupt {
+    fn from(error: RuntimeError) -> Self {
+        Interrupt::Error(error)
+    }
+}
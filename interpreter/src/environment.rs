@@ -1,30 +1,54 @@
-use super::value::{RefVal, Value};
-use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
+use super::{
+    module::RefModules,
+    value::{RefVal, Value},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 pub type RefEnv = Rc<RefCell<Environment>>;
 
 #[derive(Debug)]
 pub struct Environment {
     path: PathBuf,
+    /// File of the module, empty when the code doesn't come from a file
+    file: PathBuf,
+    modules: RefModules,
     values: HashMap<String, RefVal>,
+    exports: Vec<String>,
     parent: Option<RefEnv>,
 }
 
 impl Environment {
     pub fn new(path: PathBuf) -> Self {
+        Self::with_modules(path, RefModules::default())
+    }
+
+    pub fn with_modules(path: PathBuf, modules: RefModules) -> Self {
         Self {
             path,
+            file: PathBuf::new(),
+            modules,
             values: HashMap::new(),
+            exports: Vec::new(),
             parent: None,
         }
     }
 
     pub fn from(environment: Rc<RefCell<Environment>>) -> Self {
         let path = environment.borrow().path.clone();
+        let file = environment.borrow().file.clone();
+        let modules = environment.borrow().modules.clone();
 
         Self {
             path,
+            file,
+            modules,
             values: HashMap::new(),
+            exports: Vec::new(),
             parent: Some(environment),
         }
     }
@@ -79,4 +103,34 @@ impl Environment {
     pub fn get_path(&self) -> &PathBuf {
         &self.path
     }
+
+    pub fn get_file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn set_file(&mut self, file: &Path) {
+        self.file = file.to_path_buf();
+    }
+
+    pub fn get_modules(&self) -> RefModules {
+        self.modules.clone()
+    }
+
+    pub fn export(&mut self, name: &str) {
+        self.exports.push(name.to_owned());
+    }
+
+    /// Returns an object holding the current value of each exported binding.
+    pub fn get_exports(&self) -> Value {
+        let values = self
+            .exports
+            .iter()
+            .filter_map(|name| {
+                let value = self.values.get(name)?.borrow().clone();
+                Some((name.clone(), Rc::new(RefCell::new(value))))
+            })
+            .collect();
+
+        Value::new_object(values)
+    }
 }
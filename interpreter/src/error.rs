@@ -3,17 +3,71 @@ use std::fmt;
 use super::value::Value;
 use lexer::{errors::LexicalErrorKind, utils::Position};
 use parser::error::ParsingErrorKind;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 use thiserror::Error;
 
 #[derive(Debug, PartialEq)]
 pub struct RuntimeError {
     pub kind: RuntimeErrorKind,
     pub position: Position,
+    /// Calls the error went through, the most recent one first
+    pub frames: Vec<Frame>,
+    /// File of the module the error was raised in, when it comes from one
+    pub path: Option<PathBuf>,
 }
 
 impl RuntimeError {
     pub fn new(kind: RuntimeErrorKind, position: Position) -> Self {
-        Self { kind, position }
+        Self {
+            kind,
+            position,
+            frames: Vec::new(),
+            path: None,
+        }
+    }
+
+    /// Records the file of the module the error was raised in, unless a module
+    /// it was raised deeper in is already recorded.
+    pub fn in_file(mut self, path: &Path) -> Self {
+        if self.path.is_none() && !path.as_os_str().is_empty() {
+            self.path = Some(path.to_path_buf());
+        }
+
+        self
+    }
+
+    /// Records a call the error is unwinding through.
+    pub fn with_frame(mut self, frame: Frame) -> Self {
+        self.frames.push(frame);
+
+        self
+    }
+
+    /// Returns the value bound by a `catch` clause, thrown values are passed as
+    /// is and other errors are described by an object.
+    pub fn into_value(self) -> Value {
+        let kind = match self.kind {
+            RuntimeErrorKind::Throw(value) => return value,
+            kind => kind,
+        };
+
+        let props = [
+            ("kind", Value::String(kind.name().to_owned())),
+            ("message", Value::String(kind.to_string())),
+            ("line", Value::Integer(self.position.line_start as i64 + 1)),
+            ("col", Value::Integer(self.position.col_start as i64 + 1)),
+        ];
+        let values: HashMap<_, _> = props
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), Rc::new(RefCell::new(value))))
+            .collect();
+
+        Value::new_object(values)
     }
 }
 
@@ -23,6 +77,39 @@ impl fmt::Display for RuntimeError {
     }
 }
 
+/// Function call active when an error was raised.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Frame {
+    /// Name of the called function, `anonymous` for lambdas
+    pub name: String,
+    /// Position of the call
+    pub position: Position,
+    /// File of the module making the call, empty when it isn't from a file
+    pub path: PathBuf,
+}
+
+impl Frame {
+    pub fn new(name: &str, position: Position, path: PathBuf) -> Self {
+        Self {
+            name: name.to_owned(),
+            position,
+            path,
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in '{}' called at ", self.name)?;
+
+        if !self.path.as_os_str().is_empty() {
+            write!(f, "{}, ", self.path.display())?;
+        }
+
+        write!(f, "{}", self.position)
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum RuntimeErrorKind {
     #[error("expected '{0}', but found '{1}'")]
@@ -35,8 +122,6 @@ pub enum RuntimeErrorKind {
     UndefinedIdentifier(String),
     #[error("redeclaring existing identifier '{0}'")]
     RedeclaringIdentifier(String),
-    #[error("{0}")]
-    ControlFlow(ControlFlow),
     #[error("trying to index an unindexable object")]
     UnindexableType,
     #[error("invalid index value")]
@@ -69,16 +154,41 @@ pub enum RuntimeErrorKind {
     LexicalError(LexicalErrorKind),
     #[error("{0}")]
     ParsingError(ParsingErrorKind),
-    #[error("cannot export module in REPL mode")]
-    Export(Value),
+    #[error("uncaught exception: {0}")]
+    Throw(Value),
+    #[error("cyclic import {}", .0.join(" -> "))]
+    CyclicImport(Vec<String>),
+    #[error("module '{1}' doesn't export '{0}'")]
+    UndefinedExport(String, String),
 }
 
-#[derive(Debug, Error, PartialEq)]
-pub enum ControlFlow {
-    #[error("unexpected break statement outside of a loop")]
-    Break,
-    #[error("unexpected continue statement outside of a loop")]
-    Continue,
-    #[error("unexpected return statement outside of a function")]
-    Return(Value),
+impl RuntimeErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuntimeErrorKind::TypeExpection(_, _) => "TypeExpectation",
+            RuntimeErrorKind::InvalidOperation(_) => "InvalidOperation",
+            RuntimeErrorKind::DivisionByZero => "DivisionByZero",
+            RuntimeErrorKind::UndefinedIdentifier(_) => "UndefinedIdentifier",
+            RuntimeErrorKind::RedeclaringIdentifier(_) => "RedeclaringIdentifier",
+            RuntimeErrorKind::UnindexableType => "UnindexableType",
+            RuntimeErrorKind::InvalidIndex => "InvalidIndex",
+            RuntimeErrorKind::NotFunciton => "NotFunction",
+            RuntimeErrorKind::NotAnArray => "NotAnArray",
+            RuntimeErrorKind::NotAnObject => "NotAnObject",
+            RuntimeErrorKind::InvalidArgument(_, _) => "InvalidArgument",
+            RuntimeErrorKind::InvalidAssignment => "InvalidAssignment",
+            RuntimeErrorKind::InvalidRange => "InvalidRange",
+            RuntimeErrorKind::InvalidNumber => "InvalidNumber",
+            RuntimeErrorKind::ModuleNotFound(_) => "ModuleNotFound",
+            RuntimeErrorKind::NonIterable => "NonIterable",
+            RuntimeErrorKind::InvalidArg => "InvalidArg",
+            RuntimeErrorKind::MismatchedArg => "MismatchedArg",
+            RuntimeErrorKind::InvalidPath(_) => "InvalidPath",
+            RuntimeErrorKind::LexicalError(_) => "LexicalError",
+            RuntimeErrorKind::ParsingError(_) => "ParsingError",
+            RuntimeErrorKind::Throw(_) => "Throw",
+            RuntimeErrorKind::CyclicImport(_) => "CyclicImport",
+            RuntimeErrorKind::UndefinedExport(_, _) => "UndefinedExport",
+        }
+    }
 }
@@ -1,17 +1,33 @@
 pub mod builtin;
+pub mod completion;
 pub mod environment;
 pub mod error;
+pub mod module;
+pub mod operation;
 pub mod value;
 
-use crate::builtin::Builtin;
+use crate::builtin::{get_io_builtins, get_std_builtins, Builtin, CallContext, Caller};
 
+use completion::{Completion, Interrupt};
 use environment::{Environment, RefEnv};
-use error::{ControlFlow, RuntimeError, RuntimeErrorKind};
-use lexer::{tokens::TokenType, Lexer};
+use error::{Frame, RuntimeError, RuntimeErrorKind};
+use lexer::{
+    tokens::{Token, TokenType},
+    utils::Position,
+    Lexer,
+};
+use module::RefModules;
+use operation::*;
 use parser::{ast::*, Parser};
-use value::{Function, Range, RefVal, Value};
+use value::{Function, RefVal, Value};
 
-use std::{cell::RefCell, collections::HashMap, f64::INFINITY, ops, path::PathBuf, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 pub struct Interpreter {
     environment: RefEnv,
@@ -19,115 +35,216 @@ pub struct Interpreter {
 
 impl Interpreter {
     pub fn new(path: PathBuf) -> Self {
-        let environment = Rc::new(RefCell::new(Environment::new(path)));
+        Self::with_modules(path, RefModules::default())
+    }
+
+    /// Creates an interpreter sharing the imported modules of another one.
+    pub fn with_modules(path: PathBuf, modules: RefModules) -> Self {
+        let environment = Rc::new(RefCell::new(Environment::with_modules(path, modules)));
 
         Self { environment }
     }
 
+    /// Registers the file being run so that importing it back is reported as a
+    /// cycle.
+    pub fn set_main_module(&self, file_path: &Path) {
+        self.set_file(file_path);
+
+        if let Ok(path) = file_path.canonicalize() {
+            let modules = self.environment.borrow().get_modules();
+            let _ = modules.borrow_mut().enter(path);
+        }
+    }
+
+    /// Sets the file of the module being run, errors and tracebacks refer to
+    /// it.
+    pub fn set_file(&self, file_path: &Path) {
+        self.environment.borrow_mut().set_file(file_path);
+    }
+
     pub fn load_builtin(&self, builtins: Vec<Builtin>) {
         for builtin in builtins {
             self.environment
                 .borrow_mut()
-                .set(builtin.name, Value::Builtin(builtin));
+                .set(&builtin.name.clone(), Value::Builtin(builtin));
         }
     }
 
+    /// Exposes a Rust closure to icelang code as a global function. Pass
+    /// `f64::INFINITY as usize` as `args` to accept any number of arguments.
+    pub fn register_function<F>(&self, name: &str, args: usize, function: F)
+    where
+        F: Fn(&CallContext, &[Value]) -> Result<Value, RuntimeError> + 'static,
+    {
+        self.load_builtin(vec![Builtin::new(name, args, function)]);
+    }
+
+    pub fn set_global(&self, name: &str, value: Value) {
+        self.environment.borrow_mut().set(name, value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.environment.borrow().get(name)
+    }
+
+    /// Calls the global function `name` with already evaluated arguments.
+    pub fn call(&self, name: &str, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        let position = Position::new(0, 0, 0, 0);
+        let function = self.get_global(name).ok_or_else(|| {
+            RuntimeError::new(
+                RuntimeErrorKind::UndefinedIdentifier(name.to_owned()),
+                position,
+            )
+        })?;
+
+        self.call_value(&function, arguments)
+    }
+
+    /// Calls a function value, such as a callback handed to a native function.
+    pub fn call_value(
+        &self,
+        function: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let context = call_context(&self.environment, Position::new(0, 0, 0, 0));
+
+        context.call(function, arguments)
+    }
+
     pub fn interpret<T: Eval>(&self, node: T) -> Result<Option<Value>, RuntimeError> {
-        node.evaluate(&self.environment)
+        let completion = node
+            .evaluate(&self.environment)
+            .map_err(|error| self.in_file(error))?;
+
+        Ok(completion.into_value())
+    }
+
+    /// Runs a whole program, its functions are hoisted so they can be called
+    /// before their declaration. A top-level `return` ends the program early.
+    pub fn interpret_program(&self, nodes: &[Statement]) -> Result<Option<Value>, RuntimeError> {
+        let completion =
+            evaluate_statements(nodes, &self.environment).map_err(|error| self.in_file(error))?;
+
+        Ok(completion.into_value())
+    }
+
+    fn in_file(&self, error: RuntimeError) -> RuntimeError {
+        error.in_file(self.environment.borrow().get_file())
     }
 
     pub fn run_source(&self, source: &str) -> Result<Value, RuntimeError> {
         let tokens = match Lexer::new(source).tokenize() {
             Ok(value) => value,
             Err(error) => {
-                return Err(RuntimeError::new(
-                    RuntimeErrorKind::LexicalError(error.kind),
-                    error.position,
-                ))
+                let kind = RuntimeErrorKind::LexicalError(error.kind);
+                return Err(self.in_file(RuntimeError::new(kind, error.position)));
             }
         };
         let nodes = match Parser::new(&tokens).parse() {
             Ok(value) => value,
             Err(error) => {
-                return Err(RuntimeError::new(
-                    RuntimeErrorKind::ParsingError(error.kind),
-                    error.position,
-                ))
+                let kind = RuntimeErrorKind::ParsingError(error.kind);
+                return Err(self.in_file(RuntimeError::new(kind, error.position)));
             }
         };
 
-        for node in nodes {
-            if let Err(error) = self.interpret(node) {
-                if let RuntimeErrorKind::Export(value) = error.kind {
-                    return Ok(value);
-                } else {
-                    return Err(error);
-                }
-            }
-        }
+        self.interpret_program(&nodes)?;
 
-        Ok(Value::Null)
+        Ok(self.get_exports())
     }
-}
 
-fn is_truthy(value: &Value) -> bool {
-    match value {
-        Value::Number(value) => *value != 0.0,
-        Value::Boolean(value) => *value,
-        Value::Null => false,
-        Value::String(value) => !value.is_empty(),
-        Value::Array(value) => !value.is_empty(),
-        Value::Object(value) => !value.values.is_empty(),
-        _ => true,
+    pub fn get_exports(&self) -> Value {
+        self.environment.borrow().get_exports()
     }
 }
 
-fn get_numerical_index(expr: &Index, value: Value) -> Result<usize, RuntimeError> {
-    if let Value::Number(index) = value {
-        if index < 0.0 {
-            return Err(RuntimeError::new(
-                RuntimeErrorKind::InvalidIndex,
-                expr.token.pos,
-            ));
+/// Calls the functions that native functions call back.
+struct Evaluator;
+
+impl Caller for Evaluator {
+    fn call(
+        &self,
+        context: &CallContext,
+        function: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let position = context.position;
+        let frame = |name| Frame::new(name, position, context.get_file().to_path_buf());
+
+        match function {
+            Value::Function(function) => call_function(function, None, arguments, position)
+                .map_err(|error| error.with_frame(frame(function.get_name()))),
+            Value::Builtin(builtin) => builtin
+                .call(context, &arguments)
+                .map_err(|error| error.with_frame(frame(&builtin.name))),
+            _ => Err(context.error(RuntimeErrorKind::NotFunciton)),
         }
-        Ok(index as usize)
-    } else {
-        Err(RuntimeError::new(
-            RuntimeErrorKind::InvalidIndex,
-            expr.token.pos,
-        ))
+    }
+
+    fn run_module(
+        &self,
+        context: &CallContext,
+        file_path: &Path,
+        source: &str,
+    ) -> Result<Value, RuntimeError> {
+        let path = file_path.parent().unwrap().to_path_buf();
+        let interpreter = Interpreter::with_modules(path, context.get_modules());
+        interpreter.set_file(file_path);
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.load_builtin(get_io_builtins());
+        interpreter.run_source(source)
     }
 }
 
+fn call_context(env: &RefEnv, position: Position) -> CallContext<'static> {
+    let env = env.borrow();
+
+    CallContext::new(
+        position,
+        env.get_path().clone(),
+        env.get_file().to_path_buf(),
+        env.get_modules(),
+        &Evaluator,
+    )
+}
+
+fn call_frame(name: &str, env: &RefEnv, position: Position) -> Frame {
+    Frame::new(name, position, env.borrow().get_file().to_path_buf())
+}
+
 trait EvalRef {
-    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, RuntimeError>;
+    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, Interrupt>;
 }
 
 pub trait Eval {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError>;
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError>;
 }
 
 impl Eval for Statement {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
         self.evaluate_statement(env)
     }
 }
 
 impl Eval for Expression {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
-        Ok(Some(self.evaluate_expression(env)?))
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
+        match self.evaluate_expression(env) {
+            Ok(value) => Ok(Completion::Normal(Some(value))),
+            Err(interrupt) => interrupt.into_completion(),
+        }
     }
 }
 
 trait EvalStmt {
-    fn evaluate_statement(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError>;
+    fn evaluate_statement(&self, env: &RefEnv) -> Result<Completion, RuntimeError>;
 }
 
 impl EvalStmt for Statement {
-    fn evaluate_statement(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
+    fn evaluate_statement(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
         match self {
             Statement::ExpressionStatement(stmt) => stmt.evaluate(env),
             Statement::VariableDeclaration(stmt) => stmt.evaluate(env),
+            Statement::DestructuringDeclaration(stmt) => stmt.evaluate(env),
             Statement::BlockStatement(stmt) => stmt.evaluate(env),
             Statement::WhileStatement(stmt) => stmt.evaluate(env),
             Statement::LoopStatement(stmt) => stmt.evaluate(env),
@@ -136,12 +253,14 @@ impl EvalStmt for Statement {
             Statement::FunctionDeclaration(stmt) => stmt.evaluate(env),
             Statement::ReturnStatement(stmt) => stmt.evaluate(env),
             Statement::ForStatement(stmt) => stmt.evaluate(env),
+            Statement::TryStatement(stmt) => stmt.evaluate(env),
+            Statement::ExportStatement(stmt) => stmt.evaluate(env),
         }
     }
 }
 
 impl Eval for Declaration {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
         let name = &self.name.lexeme;
 
         if env.borrow().contains(name) {
@@ -151,32 +270,121 @@ impl Eval for Declaration {
             ));
         }
 
-        let value = self.value.evaluate_expression(env)?;
+        let value = match self.value.evaluate_expression(env) {
+            Ok(value) => value,
+            Err(interrupt) => return interrupt.into_completion(),
+        };
         env.borrow_mut().set(name, value);
 
-        Ok(None)
+        Ok(Completion::Normal(None))
     }
 }
 
-impl Eval for Block {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
-        let new_env = Rc::new(RefCell::new(Environment::from(env.clone())));
-        let len = self.statements.len();
+fn is_function_declaration(statement: &Statement) -> bool {
+    match statement {
+        Statement::FunctionDeclaration(_) => true,
+        Statement::ExportStatement(export) => {
+            matches!(*export.declaration, Statement::FunctionDeclaration(_))
+        }
+        _ => false,
+    }
+}
+
+/// Binds all the function declarations before evaluating the other statements
+/// and completes with the value of the last one, or with the first statement
+/// that did not complete normally.
+fn evaluate_statements(statements: &[Statement], env: &RefEnv) -> Result<Completion, RuntimeError> {
+    for statement in statements {
+        if is_function_declaration(statement) {
+            statement.evaluate(env)?;
+        }
+    }
+
+    let mut value = None;
+
+    for statement in statements {
+        if is_function_declaration(statement) {
+            value = None;
+            continue;
+        }
+
+        match statement.evaluate(env)? {
+            Completion::Normal(result) => value = result,
+            completion => return Ok(completion),
+        }
+    }
+
+    Ok(Completion::Normal(value))
+}
 
-        for (index, statement) in self.statements.iter().enumerate() {
-            if index == len - 1 {
-                return statement.evaluate(&new_env);
+/// Decides whether a loop goes on after an iteration completed. The `break`
+/// and `continue` targeting the loop are handled here, the other jumps end the
+/// loop and are passed to the enclosing statements.
+fn loop_flow(label: &Option<Token>, completion: Completion) -> ControlFlow<Completion> {
+    let label = label.as_ref().map(|label| label.lexeme.as_str());
+
+    match completion {
+        Completion::Normal(_) => ControlFlow::Continue(()),
+        Completion::Continue(target) if Completion::targets(&target, label) => {
+            ControlFlow::Continue(())
+        }
+        Completion::Break(target) if Completion::targets(&target, label) => {
+            ControlFlow::Break(Completion::Normal(None))
+        }
+        completion => ControlFlow::Break(completion),
+    }
+}
+
+impl Eval for Destructuring {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
+        let value = match self.value.evaluate_expression(env) {
+            Ok(value) => value,
+            Err(interrupt) => return interrupt.into_completion(),
+        };
+
+        for name in &self.names {
+            if env.borrow().contains(&name.lexeme) {
+                return Err(RuntimeError::new(
+                    RuntimeErrorKind::RedeclaringIdentifier(name.lexeme.clone()),
+                    name.pos,
+                ));
             }
-            statement.evaluate(&new_env)?;
+
+            let modules = env.borrow().get_modules();
+            let member = get_member(value.clone(), &name.lexeme, &modules, name.pos)?;
+            env.borrow_mut().set(&name.lexeme, member);
+        }
+
+        Ok(Completion::Normal(None))
+    }
+}
+
+impl Eval for Export {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
+        self.declaration.evaluate(env)?;
+
+        for name in self.get_names() {
+            env.borrow_mut().export(&name.lexeme);
         }
 
-        Ok(None)
+        Ok(Completion::Normal(None))
+    }
+}
+
+impl Eval for Block {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
+        let new_env = Rc::new(RefCell::new(Environment::from(env.clone())));
+
+        evaluate_statements(&self.statements, &new_env)
     }
 }
 
 impl Eval for For {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
-        let value = self.iterable.evaluate_expression(env)?;
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
+        let value = match self.iterable.evaluate_expression(env) {
+            Ok(value) => value,
+            Err(interrupt) => return interrupt.into_completion(),
+        };
 
         if !value.is_iterable() {
             return Err(RuntimeError::new(
@@ -185,9 +393,9 @@ impl Eval for For {
             ));
         }
 
-        let new_env = Rc::new(RefCell::new(Environment::from(env.clone())));
-
         for (key, value) in value.iter() {
+            let new_env = Rc::new(RefCell::new(Environment::from(env.clone())));
+
             if let Some(second) = &self.variable.1 {
                 new_env.borrow_mut().set(&self.variable.0.lexeme, key);
                 new_env.borrow_mut().set(&second.lexeme.clone(), value);
@@ -195,86 +403,69 @@ impl Eval for For {
                 new_env.borrow_mut().set(&self.variable.0.lexeme, value);
             }
 
-            self.block.evaluate(&new_env)?;
+            if let ControlFlow::Break(completion) =
+                loop_flow(&self.label, self.block.evaluate(&new_env)?)
+            {
+                return Ok(completion);
+            }
         }
 
-        Ok(None)
+        Ok(Completion::Normal(None))
     }
 }
 
 impl Eval for While {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
         loop {
-            let condition = self.condition.evaluate_expression(env)?;
+            let condition = match self.condition.evaluate_expression(env) {
+                Ok(value) => value,
+                Err(interrupt) => return interrupt.into_completion(),
+            };
             if !is_truthy(&condition) {
                 break;
             }
 
-            if let Some(error) = self.block.evaluate(env).err() {
-                match error.kind {
-                    RuntimeErrorKind::ControlFlow(statement) => match statement {
-                        ControlFlow::Break => break,
-                        ControlFlow::Continue => continue,
-                        ControlFlow::Return(value) => {
-                            return Err(RuntimeError::new(
-                                RuntimeErrorKind::ControlFlow(ControlFlow::Return(value)),
-                                error.position,
-                            ))
-                        }
-                    },
-                    _ => return Err(error),
-                }
+            if let ControlFlow::Break(completion) =
+                loop_flow(&self.label, self.block.evaluate(env)?)
+            {
+                return Ok(completion);
             }
         }
 
-        Ok(None)
+        Ok(Completion::Normal(None))
     }
 }
 
 impl Eval for Loop {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
         loop {
-            if let Some(error) = self.block.evaluate(env).err() {
-                match error.kind {
-                    RuntimeErrorKind::ControlFlow(statement) => match statement {
-                        ControlFlow::Break => break,
-                        ControlFlow::Continue => continue,
-                        ControlFlow::Return(value) => {
-                            return Err(RuntimeError::new(
-                                RuntimeErrorKind::ControlFlow(ControlFlow::Return(value)),
-                                error.position,
-                            ))
-                        }
-                    },
-                    _ => return Err(error),
-                }
+            if let ControlFlow::Break(completion) =
+                loop_flow(&self.label, self.block.evaluate(env)?)
+            {
+                return Ok(completion);
             }
         }
-
-        Ok(None)
     }
 }
 
 impl Eval for Break {
-    fn evaluate(&self, _env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
-        Err(RuntimeError::new(
-            RuntimeErrorKind::ControlFlow(ControlFlow::Break),
-            self.token.pos,
-        ))
+    fn evaluate(&self, _env: &RefEnv) -> Result<Completion, RuntimeError> {
+        let label = self.label.as_ref().map(|label| label.lexeme.clone());
+
+        Ok(Completion::Break(label))
     }
 }
 
 impl Eval for Continue {
-    fn evaluate(&self, _env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
-        Err(RuntimeError::new(
-            RuntimeErrorKind::ControlFlow(ControlFlow::Continue),
-            self.token.pos,
-        ))
+    fn evaluate(&self, _env: &RefEnv) -> Result<Completion, RuntimeError> {
+        let label = self.label.as_ref().map(|label| label.lexeme.clone());
+
+        Ok(Completion::Continue(label))
     }
 }
 
 impl Eval for FunctionDeclaration {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
         let token = &self.token.as_ref().unwrap();
         let name = &token.lexeme;
 
@@ -285,35 +476,47 @@ impl Eval for FunctionDeclaration {
             ));
         }
 
-        env.borrow_mut().set(
-            name,
-            Value::Function(Function {
-                declaration: self.clone(),
-            }),
-        );
+        let function = Function::new(self.clone(), env.clone());
+        env.borrow_mut().set(name, Value::Function(function));
 
-        Ok(None)
+        Ok(Completion::Normal(None))
     }
 }
 
 impl Eval for Return {
-    fn evaluate(&self, env: &RefEnv) -> Result<Option<Value>, RuntimeError> {
-        let value = self.expression.evaluate_expression(env)?;
-        Err(RuntimeError::new(
-            RuntimeErrorKind::ControlFlow(ControlFlow::Return(value)),
-            self.token.pos,
-        ))
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
+        match self.expression.evaluate_expression(env) {
+            Ok(value) => Ok(Completion::Return(value)),
+            Err(interrupt) => interrupt.into_completion(),
+        }
+    }
+}
+
+impl Eval for Try {
+    fn evaluate(&self, env: &RefEnv) -> Result<Completion, RuntimeError> {
+        match self.block.evaluate(env) {
+            Err(error) => {
+                let new_env = Rc::new(RefCell::new(Environment::from(env.clone())));
+                new_env
+                    .borrow_mut()
+                    .set(&self.error.lexeme, error.into_value());
+
+                self.handler.evaluate(&new_env)
+            }
+            result => result,
+        }
     }
 }
 
 pub trait EvalExpr {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError>;
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt>;
 }
 
 impl EvalExpr for Expression {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         match self {
             Expression::LiteralExpression(expr) => expr.evaluate_expression(env),
+            Expression::InterpolatedString(expr) => expr.evaluate_expression(env),
             Expression::AssignementExpression(expr) => expr.evaluate_expression(env),
             Expression::ArrayExpression(expr) => expr.evaluate_expression(env),
             Expression::IndexExpression(expr) => expr.evaluate_expression(env),
@@ -331,9 +534,12 @@ impl EvalExpr for Expression {
 }
 
 impl EvalExpr for Literal {
-    fn evaluate_expression(&self, _env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, _env: &RefEnv) -> Result<Value, Interrupt> {
         let value = match &self.token.value {
             TokenType::Number(value) => Value::Number(*value),
+            TokenType::Integer(value) => Value::Integer(*value),
+            TokenType::BigInt(value) => Value::BigInt(value.clone()),
+            TokenType::Decimal(value) => Value::Decimal(*value),
             TokenType::String(value) => Value::String(value.clone()),
             TokenType::Null => Value::Null,
             TokenType::True => Value::Boolean(true),
@@ -345,8 +551,25 @@ impl EvalExpr for Literal {
     }
 }
 
+impl EvalExpr for Interpolation {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
+        let mut value = String::new();
+
+        for part in &self.parts {
+            match part {
+                InterpolationPart::Literal(literal) => value.push_str(literal),
+                InterpolationPart::Expression(expression) => {
+                    value.push_str(&expression.evaluate_expression(env)?.to_string())
+                }
+            }
+        }
+
+        Ok(Value::String(value))
+    }
+}
+
 impl EvalExpr for Variable {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let name = &self.token.lexeme;
 
         match env.borrow().get(name) {
@@ -354,13 +577,14 @@ impl EvalExpr for Variable {
             None => Err(RuntimeError::new(
                 RuntimeErrorKind::UndefinedIdentifier(self.token.lexeme.clone()),
                 self.token.pos,
-            )),
+            )
+            .into()),
         }
     }
 }
 
 impl EvalRef for Variable {
-    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, RuntimeError> {
+    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, Interrupt> {
         let name = &self.token.lexeme;
 
         match env.borrow().get_ref(name) {
@@ -368,13 +592,14 @@ impl EvalRef for Variable {
             None => Err(RuntimeError::new(
                 RuntimeErrorKind::UndefinedIdentifier(self.token.lexeme.clone()),
                 self.token.pos,
-            )),
+            )
+            .into()),
         }
     }
 }
 
 impl EvalExpr for Assign {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let expression_value = self.value.evaluate_expression(env)?;
 
         let rf = match &*self.left {
@@ -384,36 +609,17 @@ impl EvalExpr for Assign {
             _ => unreachable!(),
         };
 
-        if let TokenType::Equal = &self.token.value {
-            *rf.borrow_mut() = expression_value.clone();
-            Ok(expression_value)
-        } else {
-            let prev = rf.borrow().clone();
-            let result = match self.token.value {
-                TokenType::PlusEqual => prev + expression_value,
-                TokenType::MinusEqaul => prev - expression_value,
-                TokenType::AsterixEqual => prev * expression_value,
-                TokenType::SlashEqual => prev / expression_value,
-                TokenType::ModuloEqual => prev % expression_value,
-                _ => unreachable!(),
-            };
-
-            match result {
-                Some(result) => {
-                    *rf.borrow_mut() = result.clone();
-                    Ok(result)
-                }
-                None => Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidAssignment,
-                    self.token.pos,
-                )),
-            }
-        }
+        Ok(assign_operation(
+            &rf,
+            &self.token.value,
+            expression_value,
+            self.token.pos,
+        )?)
     }
 }
 
 impl EvalExpr for Array {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let mut array: Vec<RefVal> = vec![];
 
         for item in &self.items {
@@ -422,12 +628,12 @@ impl EvalExpr for Array {
             array.push(rf);
         }
 
-        Ok(Value::Array(array))
+        Ok(Value::new_array(array))
     }
 }
 
 impl EvalExpr for Object {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let mut values: HashMap<String, RefVal> = HashMap::new();
 
         for (token, expression) in &self.props {
@@ -440,318 +646,127 @@ impl EvalExpr for Object {
             values.insert(name.to_owned(), rf);
         }
 
-        Ok(Value::Object(self::value::Object { values }))
+        Ok(Value::new_object(values))
     }
 }
 
 impl EvalExpr for Index {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let expression = self.expression.evaluate_expression(env)?;
         let index_expression = self.index.evaluate_expression(env)?;
 
-        if let Value::Object(object) = expression {
-            let index = match index_expression {
-                Value::Number(value) => value.to_string(),
-                Value::String(value) => value,
-                _ => {
-                    return Err(RuntimeError::new(
-                        RuntimeErrorKind::UndefinedIdentifier(self.token.lexeme.clone()),
-                        self.token.pos,
-                    ))
-                }
-            };
-
-            let value = if let Some(value) = object.values.get(&index) {
-                value.borrow().clone()
-            } else {
-                Value::Null
-            };
-
-            Ok(value)
-        } else {
-            let index = get_numerical_index(self, index_expression)?;
-            let value = match expression {
-                Value::Array(array) => {
-                    if let Some(value) = array.get(index) {
-                        value.borrow().clone()
-                    } else {
-                        Value::Null
-                    }
-                }
-                Value::String(string) => match string.chars().nth(index) {
-                    Some(value) => Value::String(value.to_string()),
-                    None => Value::Null,
-                },
-                _ => {
-                    return Err(RuntimeError::new(
-                        RuntimeErrorKind::UnindexableType,
-                        self.token.pos,
-                    ))
-                }
-            };
-
-            Ok(value)
-        }
+        Ok(get_index(expression, index_expression, self.token.pos)?)
     }
 }
 
 impl EvalRef for Index {
-    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, RuntimeError> {
+    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, Interrupt> {
         let expression_ref = match &*self.expression {
             Expression::VariableExpression(variable) => variable.evaluate_ref(env)?,
             Expression::IndexExpression(index_expr) => index_expr.evaluate_ref(env)?,
             Expression::PropAccess(prop) => prop.evaluate_ref(env)?,
             _ => {
-                return Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidAssignment,
-                    self.token.pos,
-                ))
+                return Err(
+                    RuntimeError::new(RuntimeErrorKind::InvalidAssignment, self.token.pos).into(),
+                )
             }
         };
         let index_expression = self.index.evaluate_expression(env)?;
-        let expression = &mut *expression_ref.borrow_mut();
-
-        match expression {
-            Value::Array(array) => {
-                let index = get_numerical_index(self, index_expression)?;
-                if index >= array.len() {
-                    array.resize_with(index + 1, || Rc::new(RefCell::new(Value::Null)))
-                }
+        let expression = &*expression_ref.borrow();
 
-                Ok(array[index].clone())
-            }
-            Value::Object(object) => {
-                let index = match index_expression {
-                    Value::Number(value) => value.to_string(),
-                    Value::String(value) => value,
-                    _ => {
-                        return Err(RuntimeError::new(
-                            RuntimeErrorKind::InvalidIndex,
-                            self.token.pos,
-                        ))
-                    }
-                };
-
-                if let Some(value) = object.values.get(&index) {
-                    Ok(value.clone())
-                } else {
-                    let rf = Rc::new(RefCell::new(Value::Null));
-                    object.values.insert(index, rf.clone());
-                    Ok(rf)
-                }
-            }
-            _ => Err(RuntimeError::new(
-                RuntimeErrorKind::InvalidAssignment,
-                self.token.pos,
-            )),
-        }
+        Ok(get_index_ref(expression, index_expression, self.token.pos)?)
     }
 }
 
 impl EvalExpr for Access {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let expression = self.expression.evaluate_expression(env)?;
-        let property = &self.prop.lexeme;
-
-        let value = match expression {
-            Value::Object(object) => {
-                if let Some(value) = object.values.get(property) {
-                    value.borrow().clone()
-                } else {
-                    Value::Null
-                }
-            }
-            _ => {
-                return Err(RuntimeError::new(
-                    RuntimeErrorKind::NotAnObject,
-                    self.token.pos,
-                ))
-            }
-        };
 
-        Ok(value)
+        Ok(get_property(expression, &self.prop.lexeme, self.token.pos)?)
     }
 }
 
 impl EvalRef for Access {
-    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, RuntimeError> {
+    fn evaluate_ref(&self, env: &RefEnv) -> Result<RefVal, Interrupt> {
         let expression_ref = match &*self.expression {
             Expression::VariableExpression(variable) => variable.evaluate_ref(env)?,
             Expression::IndexExpression(index_expr) => index_expr.evaluate_ref(env)?,
             Expression::PropAccess(prop) => prop.evaluate_ref(env)?,
             _ => {
-                return Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidAssignment,
-                    self.token.pos,
-                ))
+                return Err(
+                    RuntimeError::new(RuntimeErrorKind::InvalidAssignment, self.token.pos).into(),
+                )
             }
         };
-        let expression = &mut *expression_ref.borrow_mut();
-
-        match expression {
-            Value::Object(object) => {
-                let prop = &self.prop.lexeme;
-                match object.values.get(prop) {
-                    Some(value) => Ok(value.clone()),
-                    None => {
-                        let rf = Rc::new(RefCell::new(Value::Null));
-                        object.values.insert(prop.to_owned(), rf.clone());
-                        Ok(rf)
-                    }
-                }
-            }
-            _ => Err(RuntimeError::new(
-                RuntimeErrorKind::NotAnObject,
-                self.token.pos,
-            )),
-        }
+        let expression = &*expression_ref.borrow();
+
+        Ok(get_property_ref(
+            expression,
+            &self.prop.lexeme,
+            self.token.pos,
+        )?)
     }
 }
 
 impl EvalExpr for Unary {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let operand = self.operand.evaluate_expression(env)?;
-        match &self.operator.value {
-            TokenType::Minus => match operand {
-                Value::Number(value) => Ok(Value::Number(-value)),
-                _ => Err(RuntimeError::new(
-                    RuntimeErrorKind::TypeExpection("number".to_string(), operand.get_type()),
-                    self.operator.pos,
-                )),
-            },
-            TokenType::Bang => Ok(Value::Boolean(!is_truthy(&operand))),
-            _ => unreachable!(),
-        }
+
+        Ok(unary_operation(
+            &self.operator.value,
+            operand,
+            self.operator.pos,
+        )?)
     }
 }
 
 impl EvalExpr for Binary {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let left = self.left.evaluate_expression(env)?;
-        let right = self.right.evaluate_expression(env)?;
-        let left_type = left.get_type();
-        let right_type = right.get_type();
-
-        match &self.operator.value {
-            TokenType::Asterix => match left * right {
-                Some(value) => Ok(value),
-                None => Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidOperation(format!(
-                        "cannot multiply a '{}' by a '{}'",
-                        left_type, right_type
-                    )),
-                    self.operator.pos,
-                )),
-            },
-            TokenType::Slash => {
-                if let Value::Number(right) = right {
-                    if right == 0.0 {
-                        return Err(RuntimeError::new(
-                            RuntimeErrorKind::DivisionByZero,
-                            self.operator.pos,
-                        ));
-                    }
-                }
 
-                match left / right {
-                    Some(value) => Ok(value),
-                    None => Err(RuntimeError::new(
-                        RuntimeErrorKind::InvalidOperation(format!(
-                            "cannot divide a '{}' by a '{}'",
-                            left_type, right_type
-                        )),
-                        self.operator.pos,
-                    )),
-                }
-            }
-            TokenType::Minus => match left - right {
-                Some(value) => Ok(value),
-                None => Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidOperation(format!(
-                        "cannot substract a '{}' by a '{}'",
-                        left_type, right_type
-                    )),
-                    self.operator.pos,
-                )),
-            },
-            TokenType::Plus => match left + right {
-                Some(value) => Ok(value),
-                None => Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidOperation(format!(
-                        "cannot add a '{}' by a '{}'",
-                        left_type, right_type
-                    )),
-                    self.operator.pos,
-                )),
-            },
-            TokenType::Modulo => match left % right {
-                Some(value) => Ok(value),
-                None => Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidOperation(format!(
-                        "cannot divide a '{}' by a '{}'",
-                        left_type, right_type
-                    )),
-                    self.operator.pos,
-                )),
-            },
-            TokenType::Greater => Ok(Value::Boolean(left > right)),
-            TokenType::GreaterEqual => Ok(Value::Boolean(left >= right)),
-            TokenType::Less => Ok(Value::Boolean(left < right)),
-            TokenType::LessEqual => Ok(Value::Boolean(left <= right)),
-            TokenType::EqualEqual => Ok(Value::Boolean(left == right)),
-            TokenType::BangEqual => Ok(Value::Boolean(left != right)),
-            TokenType::And => Ok(Value::Boolean(is_truthy(&left) && is_truthy(&right))),
-            TokenType::Or => Ok(Value::Boolean(is_truthy(&left) || is_truthy(&right))),
-            TokenType::To => match (left, right) {
-                (Value::Number(start), Value::Number(end)) => {
-                    let start = start as i64;
-                    let end = end as i64;
-                    let range = Range::NumberRange(ops::Range { start, end });
-                    Ok(Value::Range(range))
-                }
-                (Value::String(start), Value::String(end)) => {
-                    if start.len() == 1 && end.len() == 1 {
-                        let start = start.chars().next().unwrap();
-                        let end = end.chars().next().unwrap();
-                        let range = Range::CharRange(ops::Range { start, end });
-                        Ok(Value::Range(range))
-                    } else {
-                        Err(RuntimeError::new(
-                            RuntimeErrorKind::InvalidRange,
-                            self.operator.pos,
-                        ))
-                    }
-                }
-                _ => Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidRange,
-                    self.operator.pos,
-                )),
-            },
-            _ => unreachable!(),
+        match self.operator.value {
+            TokenType::And if !is_truthy(&left) => return Ok(left),
+            TokenType::Or if is_truthy(&left) => return Ok(left),
+            TokenType::And | TokenType::Or => return self.right.evaluate_expression(env),
+            _ => {}
         }
+
+        let right = self.right.evaluate_expression(env)?;
+
+        Ok(binary_operation(
+            &self.operator.value,
+            left,
+            right,
+            self.operator.pos,
+        )?)
     }
 }
 
 impl EvalExpr for If {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let condition = self.condition.evaluate_expression(env)?;
-        let mut value = None;
 
         if is_truthy(&condition) {
-            value = self.true_branch.evaluate(env)?;
+            branch_value(self.true_branch.evaluate(env)?)
         } else if let Some(else_branch) = &self.else_branch {
-            value = else_branch.evaluate(env)?;
+            branch_value(else_branch.evaluate(env)?)
+        } else {
+            Ok(Value::Null)
         }
+    }
+}
 
-        match value {
-            Some(value) => Ok(value),
-            None => Ok(Value::Null),
-        }
+/// The value of the branch of an `if` or a `match`. A branch ending with a
+/// `break`, `continue` or `return` interrupts the expression.
+fn branch_value(completion: Completion) -> Result<Value, Interrupt> {
+    match completion {
+        Completion::Normal(value) => Ok(value.unwrap_or(Value::Null)),
+        completion => Err(Interrupt::Completion(completion)),
     }
 }
 
 impl EvalExpr for Match {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let match_pattern = self.pattern.evaluate_expression(env)?;
 
         for arm in &self.arms {
@@ -759,19 +774,13 @@ impl EvalExpr for Match {
                 let value = pattern.evaluate_expression(env)?;
 
                 if match_pattern == value {
-                    match &arm.block.evaluate(env)? {
-                        Some(value) => return Ok(value.clone()),
-                        None => return Ok(Value::Null),
-                    }
+                    return branch_value(arm.block.evaluate(env)?);
                 }
             }
         }
 
         if let Some(defalut) = &self.default {
-            match defalut.block.evaluate(env)? {
-                Some(value) => return Ok(value),
-                None => return Ok(Value::Null),
-            }
+            return branch_value(defalut.block.evaluate(env)?);
         }
 
         Ok(Value::Null)
@@ -779,80 +788,57 @@ impl EvalExpr for Match {
 }
 
 impl EvalExpr for Lambda {
-    fn evaluate_expression(&self, _env: &RefEnv) -> Result<Value, RuntimeError> {
-        let lambda = Value::Function(Function {
-            declaration: FunctionDeclaration {
-                token: None,
-                parameter: self.parameter.clone(),
-                body: self.body.clone(),
-            },
-        });
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
+        let declaration = FunctionDeclaration {
+            token: None,
+            parameter: self.parameter.clone(),
+            body: self.body.clone(),
+            span: self.span,
+        };
+        let lambda = Value::Function(Function::new(declaration, env.clone()));
 
         Ok(lambda)
     }
 }
 
 impl EvalExpr for Call {
-    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, RuntimeError> {
+    fn evaluate_expression(&self, env: &RefEnv) -> Result<Value, Interrupt> {
         let value = self.caller.evaluate_expression(env)?;
         let got = self.arguments.len();
 
         if let Value::Function(function) = value {
-            let new_env = Rc::new(RefCell::new(Environment::from(env.clone())));
-            let expected = function.declaration.parameter.len();
-
-            if expected != got {
-                return Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidArgument(expected, got),
-                    self.token.pos,
-                ));
-            }
-
-            if let Some(object) = &self.object {
-                let object_value = object.evaluate_expression(env)?;
-                new_env.borrow_mut().set("self", object_value);
-            }
+            let this = match &self.object {
+                Some(object) => Some(object.evaluate_expression(env)?),
+                None => None,
+            };
+            let mut arguments = Vec::with_capacity(got);
 
-            for (index, arg) in self.arguments.iter().enumerate() {
-                let arg_value = arg.evaluate_expression(env)?;
-                let arg_name = &function.declaration.parameter[index].lexeme;
-                new_env.borrow_mut().set(arg_name, arg_value);
+            for arg in &self.arguments {
+                arguments.push(arg.evaluate_expression(env)?);
             }
 
-            let value = function.declaration.body.evaluate(&new_env);
-            let value = match value {
-                Ok(value) => value,
-                Err(error) => {
-                    if let RuntimeErrorKind::ControlFlow(ControlFlow::Return(value)) = error.kind {
-                        Some(value)
-                    } else {
-                        return Err(error);
-                    }
-                }
-            };
+            let value =
+                call_function(&function, this, arguments, self.token.pos).map_err(|error| {
+                    error.with_frame(call_frame(function.get_name(), env, self.token.pos))
+                })?;
 
-            match value {
-                Some(value) => Ok(value),
-                None => Ok(Value::Null),
-            }
+            Ok(value)
         } else if let Value::Builtin(builtin) = value {
-            let expected = builtin.args;
+            let mut arguments = Vec::with_capacity(got);
 
-            if expected != INFINITY as usize && got != expected {
-                return Err(RuntimeError::new(
-                    RuntimeErrorKind::InvalidArgument(expected, got),
-                    self.token.pos,
-                ));
+            for arg in &self.arguments {
+                arguments.push(arg.evaluate_expression(env)?);
             }
 
-            let value = (builtin.function)(env, &self.token, &self.arguments)?;
+            let value = builtin
+                .call(&call_context(env, self.token.pos), &arguments)
+                .map_err(|error| {
+                    error.with_frame(call_frame(&builtin.name, env, self.token.pos))
+                })?;
 
             Ok(value)
         } else {
-            return Err(RuntimeError::new(
-                RuntimeErrorKind::NotFunciton,
-                self.token.pos,
-            ));
+            Err(RuntimeError::new(RuntimeErrorKind::NotFunciton, self.token.pos).into())
         }
     }
 }
@@ -860,12 +846,15 @@ impl EvalExpr for Call {
 #[cfg(test)]
 #[allow(unused_must_use)]
 mod test {
-    use crate::value::Object;
+    use crate::{
+        builtin::{get_io_builtins, get_std_builtins},
+        error::RuntimeErrorKind,
+    };
 
     use super::{Interpreter, Value};
     use lexer::Lexer;
     use parser::Parser;
-    use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
+    use std::{cell::RefCell, collections::HashMap, env, fs, path::PathBuf, rc::Rc};
 
     #[test]
     fn test_eval_operations() {
@@ -888,6 +877,40 @@ mod test {
         assert_eq!(get("c"), Value::Boolean(true));
     }
 
+    #[test]
+    fn test_short_circuit() {
+        let source = "
+            set calls = 0;
+            set count = lambda() {
+                calls += 1;
+                true
+            };
+            set a = null or 'default';
+            set b = 0 and count();
+            set c = 'value' or count();
+            set d = null and null.next;
+            set e = 1 and 2;
+            set f = false or null;
+            set g = count() and 'counted';
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        for node in ast {
+            interpreter.interpret(node).unwrap();
+        }
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("a"), Value::String("default".to_owned()));
+        assert_eq!(get("b"), Value::Number(0.0));
+        assert_eq!(get("c"), Value::String("value".to_owned()));
+        assert_eq!(get("d"), Value::Null);
+        assert_eq!(get("e"), Value::Number(2.0));
+        assert_eq!(get("f"), Value::Null);
+        assert_eq!(get("g"), Value::String("counted".to_owned()));
+        assert_eq!(get("calls"), Value::Number(1.0));
+    }
+
     #[test]
     fn test_eval_block() {
         let source = "
@@ -910,7 +933,7 @@ mod test {
         let contains = |name| interpreter.environment.as_ref().borrow().contains(name);
 
         assert_eq!(get("a"), Value::Number(3.0));
-        assert_eq!(contains("b"), false);
+        assert!(!contains("b"));
     }
 
     #[test]
@@ -1007,6 +1030,54 @@ mod test {
         assert_eq!(get("i"), Value::Number(6.0));
     }
 
+    #[test]
+    fn test_labelled_loops() {
+        let source = "
+            set found = null;
+            for n in [1, 2, 3] {
+                if n == 2 { break }
+                found = n;
+            }
+
+            set pairs = '';
+            outer: for i in 0 to 3 {
+                set j = 0;
+                loop {
+                    j += 1;
+                    if j > i { continue outer }
+                    if i + j == 4 { break outer }
+                    pairs += '{i}{j} ';
+                }
+            }
+
+            function first_even(items) {
+                for item in items {
+                    set parity = match item % 2 {
+                        0: return item,
+                        _: 'odd',
+                    };
+                }
+                null
+            }
+            set even = first_even([3, 5, 8, 9]);
+
+            return even * 10
+            set unreachable = true;
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        let value = interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+        let contains = |name| interpreter.environment.as_ref().borrow().contains(name);
+
+        assert_eq!(get("found"), Value::Number(1.0));
+        assert_eq!(get("pairs"), Value::String("11 21 ".to_owned()));
+        assert_eq!(get("even"), Value::Number(8.0));
+        assert_eq!(value, Some(Value::Number(80.0)));
+        assert!(!contains("unreachable"));
+    }
+
     #[test]
 
     fn test_match() {
@@ -1075,10 +1146,10 @@ mod test {
 
         assert_eq!(
             get("a"),
-            Value::Array(vec![
+            Value::new_array(vec![
                 Rc::new(RefCell::new(Value::Number(0.0))),
                 Rc::new(RefCell::new(Value::Number(1.0))),
-                Rc::new(RefCell::new(Value::Array(vec![
+                Rc::new(RefCell::new(Value::new_array(vec![
                     Rc::new(RefCell::new(Value::Number(0.0))),
                     Rc::new(RefCell::new(Value::Number(1.0))),
                     Rc::new(RefCell::new(Value::Number(2.0))),
@@ -1147,6 +1218,112 @@ mod test {
         assert_eq!(get("b"), Value::String("here".to_owned()));
     }
 
+    #[test]
+    fn test_hoisting() {
+        let source = "
+            set a = add(1, 2);
+
+            function add(a, b) {
+                a + b
+            }
+
+            function is_even(n) {
+                if n == 0 { true } else { is_odd(n - 1) }
+            }
+
+            function is_odd(n) {
+                if n == 0 { false } else { is_even(n - 1) }
+            }
+
+            function compute() {
+                set value = twice(3);
+
+                function twice(n) {
+                    double(n) * 2
+                }
+
+                function double(n) {
+                    n * 2
+                }
+
+                value
+            }
+            set b = compute();
+            set c = is_even(10);
+            set d = is_odd(7);
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("a"), Value::Number(3.0));
+        assert_eq!(get("b"), Value::Number(12.0));
+        assert_eq!(get("c"), Value::Boolean(true));
+        assert_eq!(get("d"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_closure() {
+        let source = "
+            function counter() {
+                set count = 0;
+                lambda() {
+                    count += 1;
+                    count
+                }
+            }
+            set next = counter();
+            next();
+            set a = next();
+            set b = counter()();
+
+            function adder(x) {
+                lambda(y) lambda(z) x + y + z
+            }
+            set c = adder(1)(2)(3);
+
+            function account() {
+                set balance = 0;
+                {
+                    deposit: lambda(n) { balance += n },
+                    get: lambda() balance,
+                }
+            }
+            set acc = account();
+            acc.deposit(5);
+            acc.deposit(10);
+            set d = acc.get();
+
+            function make_fact() {
+                set fact = lambda(n) if n <= 1 { 1 } else { n * fact(n - 1) };
+                fact
+            }
+            set e = make_fact()(5);
+
+            set callbacks = [];
+            for i in 3 {
+                callbacks[i] = lambda() i;
+            }
+            set f = callbacks[1]();
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        for node in ast {
+            interpreter.interpret(node);
+        }
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("a"), Value::Number(2.0));
+        assert_eq!(get("b"), Value::Number(1.0));
+        assert_eq!(get("c"), Value::Number(6.0));
+        assert_eq!(get("d"), Value::Number(15.0));
+        assert_eq!(get("e"), Value::Number(120.0));
+        assert_eq!(get("f"), Value::Number(1.0));
+    }
+
     #[test]
     fn test_object() {
         let source = "
@@ -1196,19 +1373,15 @@ mod test {
         }
         let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
 
-        let mut object = Object {
-            values: HashMap::new(),
-        };
+        let mut values = HashMap::new();
 
-        object.values.insert(
+        values.insert(
             "among".to_owned(),
             Rc::new(RefCell::new(Value::Number(0.0))),
         );
-        object
-            .values
-            .insert("us".to_owned(), Rc::new(RefCell::new(Value::Number(1.0))));
+        values.insert("us".to_owned(), Rc::new(RefCell::new(Value::Number(1.0))));
 
-        assert_eq!(get("o"), Value::Object(object));
+        assert_eq!(get("o"), Value::new_object(values));
     }
 
     #[test]
@@ -1229,4 +1402,657 @@ mod test {
 
         assert_eq!(get("factorial"), Value::Number(120.0));
     }
+
+    #[test]
+    fn test_integers() {
+        let source = "
+            set a = 7 / 2;
+            set b = 6 / 3;
+            set c = -7 % 3;
+            set d = 9223372036854775807 + 1;
+            set e = 1 + 0.5;
+            set f = [int(3.9), int(-3.9), int('42'), float(2), floor(2.5)];
+            set g = '{2.0} {2} {2.5} {-0.5}';
+            set h = [1 == 1.0, 2 > 1.5, type_of(1), type_of(1.0), is_integer(1), is_integer(1.0)];
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+        let items = |name| match get(name) {
+            Value::Array(items) => items
+                .borrow()
+                .iter()
+                .map(|item| item.borrow().clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        assert!(matches!(get("a"), Value::Number(value) if value == 3.5));
+        assert!(matches!(get("b"), Value::Integer(2)));
+        assert!(matches!(get("c"), Value::Integer(-1)));
+        assert!(matches!(get("d"), Value::Number(value) if value == 9223372036854775808.0));
+        assert!(matches!(get("e"), Value::Number(value) if value == 1.5));
+        assert!(matches!(
+            items("f")[..],
+            [
+                Value::Integer(3),
+                Value::Integer(-3),
+                Value::Integer(42),
+                Value::Number(_),
+                Value::Integer(2)
+            ]
+        ));
+        assert_eq!(get("g"), Value::String("2 2 2.5 -0.5".to_owned()));
+        assert_eq!(
+            items("h"),
+            [
+                Value::Boolean(true),
+                Value::Boolean(true),
+                Value::String("number".to_owned()),
+                Value::String("number".to_owned()),
+                Value::Boolean(true),
+                Value::Boolean(false),
+            ]
+        );
+
+        let errors = [
+            ("[1, 2][0.5]", RuntimeErrorKind::InvalidIndex),
+            ("1 % 0", RuntimeErrorKind::DivisionByZero),
+            ("0 to 1.5", RuntimeErrorKind::InvalidRange),
+            ("int('a')", RuntimeErrorKind::InvalidNumber),
+        ];
+
+        for (source, kind) in errors {
+            let error = interpreter.run_source(source).unwrap_err();
+            assert_eq!(error.kind, kind, "error of '{source}'");
+        }
+    }
+
+    #[test]
+    fn test_references() {
+        let source = "
+            function append(items, item) {
+                items[length(items)] = item;
+            }
+            set a = [1, [2]];
+            set b = a;
+            append(b, 3);
+            set c = clone(a);
+            set d = deep_clone(a);
+            c[0] = 'c';
+            c[1][0] = 'shared';
+            d[1][0] = 'copied';
+            set e = { name: 'e' };
+            set f = { inner: e, list: a };
+            f.inner.name = 'renamed';
+            set g = [a == b, a == deep_clone(a), a == c];
+            set h = [1];
+            h[1] = h;
+            set i = deep_clone(h);
+            set j = [i[1] == i, i[1][1][0], h == h];
+            set k = [];
+            for item in k {
+                k[length(k)] = item;
+            }
+            set l = [1, 2];
+            for item in l {
+                l[length(l)] = item;
+            }
+            set m = [h == [1, [2]], h == [1, [1, [3]]], h == i, h == [1, h]];
+            set o = { x: 1 };
+            o.self = o;
+            set p = [o == { x: 1, self: { x: 2 } }, o == { x: 1, self: o }];
+            set q = [contains([h], [1, [2]]), index_of([h], [1, [1, [3]]]), index_of([h], i)];
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("a").to_string(), "[1, [shared], 3]");
+        assert_eq!(get("b").to_string(), "[1, [shared], 3]");
+        assert_eq!(get("c").to_string(), "[c, [shared], 3]");
+        assert_eq!(get("d").to_string(), "[1, [copied], 3]");
+        assert_eq!(get("e").to_string(), "{ name: renamed }");
+        assert_eq!(get("g").to_string(), "[true, true, false]");
+        assert_eq!(get("h").to_string(), "[1, [...]]");
+        assert_eq!(get("j").to_string(), "[true, 1, true]");
+        assert_eq!(get("l").to_string(), "[1, 2, 1, 2]");
+        assert_eq!(get("m").to_string(), "[false, false, true, true]");
+        assert_eq!(get("p").to_string(), "[false, true]");
+        assert_eq!(get("q").to_string(), "[false, -1, 0]");
+
+        let (Value::Array(h), Value::Array(i)) = (get("h"), get("i")) else {
+            panic!("h and i should be arrays");
+        };
+        assert!(!Rc::ptr_eq(&h, &i));
+        assert!(matches!(&*i.borrow()[1].borrow(), Value::Array(inner) if Rc::ptr_eq(inner, &i)));
+    }
+
+    #[test]
+    fn test_arrays() {
+        let source = "
+            function add(items, item) {
+                push(items, item);
+            }
+            set a = [1, 2];
+            add(a, 3);
+            insert(a, 0, 0);
+            insert(a, 4, 4);
+            set b = [pop(a), remove(a, 1), pop([])];
+            set c = [slice(a, 1, 2), slice(a, 1, 10), slice(a, 2, 1), concat(a, [[5]])];
+            set d = [index_of(a, 3), index_of(a, 7), index_of([[1]], [1]), contains(a, 0), contains(a, 9)];
+            set e = [3, 1.5, 2n, -1];
+            sort(e);
+            set f = ['pear', 'fig', 'apple'];
+            sort(f);
+            reverse(f);
+            set g = [{ n: 2, k: 'a' }, { n: 1, k: 'b' }, { n: 2, k: 'c' }, { n: 1, k: 'd' }];
+            sort_by(g, lambda(x, y) x.n - y.n);
+            set h = '';
+            for item in g {
+                h += item.k;
+            }
+            set i = [3, 1, 2];
+            sort_by(i, lambda(x, y) {
+                y - x + length(i) - 3
+            });
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("a").to_string(), "[0, 2, 3]");
+        assert_eq!(get("b").to_string(), "[4, 1, null]");
+        assert_eq!(get("c").to_string(), "[[2], [2, 3], [], [0, 2, 3, [5]]]");
+        assert_eq!(get("d").to_string(), "[2, -1, 0, true, false]");
+        assert_eq!(get("e").to_string(), "[-1, 1.5, 2, 3]");
+        assert_eq!(get("f").to_string(), "[pear, fig, apple]");
+        assert_eq!(get("h"), Value::String("bdac".to_owned()));
+        assert_eq!(get("i").to_string(), "[3, 2, 1]");
+
+        let type_error = |expected: &str, found: &str| {
+            RuntimeErrorKind::TypeExpection(expected.to_owned(), found.to_owned())
+        };
+        let errors = [
+            ("push('a', 1)", type_error("array", "string")),
+            ("insert([1], 2, 0)", RuntimeErrorKind::InvalidIndex),
+            ("remove([1], 1)", RuntimeErrorKind::InvalidIndex),
+            ("slice([1], -1, 1)", RuntimeErrorKind::InvalidIndex),
+            ("sort([1, 'a'])", type_error("string", "number")),
+            ("sort([1, null])", type_error("number or string", "null")),
+            ("sort([null, 1])", type_error("number or string", "null")),
+            (
+                "sort(['a', 2, [1]])",
+                type_error("number or string", "array"),
+            ),
+            (
+                "sort_by([1, 2], lambda(x, y) 'a')",
+                type_error("number", "string"),
+            ),
+            (
+                "sort_by([1, 2], lambda(x) 0)",
+                RuntimeErrorKind::InvalidArgument(1, 2),
+            ),
+            (
+                "set j = [1, 2]; sort_by(j, lambda(x, y) { push(j, 0); x - y })",
+                RuntimeErrorKind::InvalidOperation("array modified during sort".to_owned()),
+            ),
+            (
+                "set k = [1, 2]; sort_by(k, lambda(x, y) { k[0] = 3; x - y })",
+                RuntimeErrorKind::InvalidOperation("array modified during sort".to_owned()),
+            ),
+        ];
+
+        for (source, kind) in errors {
+            let error = interpreter.run_source(source).unwrap_err();
+            assert_eq!(error.kind, kind, "error of '{source}'");
+        }
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let source = "
+            set f = lambda(x) x;
+            set g = lambda(x) x;
+            set h = f;
+            function make() {
+                lambda() 1
+            }
+            set a = [[1, [2, 3]] == [1, [2, 3]], [1, 2] == [1, 3], [1] == [1.0]];
+            set b = [{ a: [1], b: 'x' } == { b: 'x', a: [1] }, { a: 1 } == { a: 1, b: 2 }];
+            set c = [f == f, f == h, f == g, make() == make(), type_of == type_of, type_of == length];
+            set d = ['apple' < 'banana', 2 <= 2.0, 1n < 1.5, 'b' >= 'a'];
+            set e = [1 == '1', null == null, null == false, 0 to 2 == 0 to 2];
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("a").to_string(), "[true, false, true]");
+        assert_eq!(get("b").to_string(), "[true, false]");
+        assert_eq!(
+            get("c").to_string(),
+            "[true, true, false, false, true, false]"
+        );
+        assert_eq!(get("d").to_string(), "[true, true, true, true]");
+        assert_eq!(get("e").to_string(), "[false, true, false, true]");
+
+        let type_error = |expected: &str, found: &str| {
+            RuntimeErrorKind::TypeExpection(expected.to_owned(), found.to_owned())
+        };
+        let errors = [
+            ("[1] < [2]", type_error("number or string", "array")),
+            ("1 < 'a'", type_error("number", "string")),
+            ("'a' >= null", type_error("string", "null")),
+            ("f > g", type_error("number or string", "function")),
+            ("{} <= {}", type_error("number or string", "object")),
+        ];
+
+        for (source, kind) in errors {
+            let error = interpreter.run_source(source).unwrap_err();
+            assert_eq!(error.kind, kind, "error of '{source}'");
+        }
+    }
+
+    #[test]
+    fn test_exact_numbers() {
+        let source = "
+            set a = 0.1m + 0.2m;
+            set b = [0.1 + 0.2 == 0.3, a == 0.3m, a == 0.3];
+            set c = 9223372036854775807n + 1;
+            set d = [19.99m * 3, 1m / 4, 10n / 4, 2n * 0.5, -0.50m];
+            set e = [type_of(1n), type_of(1m), type_of(1n + 1), type_of(1m + 1n), type_of(1m + 1.0), is_number(1m)];
+            set f = [parse_number('12n'), parse_number('0.10m'), decimal('0.1'), decimal(0.1), parse_number('99999999999999999999'), type_of(parse_number('-99999999999999999999')), parse_number('1e20')];
+            set g = [bigint('123456789012345678901234567890'), bigint(2.7), int(2.7m)];
+            set h = [round(2.5m), floor(-2.5m), ceil(2.1m), pow(2n, 70), pow(1.1m, 2)];
+            set i = [1n < 2, 1.5m > 1, 2n > 1.5, 1n == 1, 1m == 1n, -1m < 0];
+            set j = [123456789012345678901234567891n / 3n, -7n / 2, 7n % -2, 10n / 4.0];
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert!(matches!(get("a"), Value::Decimal(_)));
+        assert_eq!(get("a").to_string(), "0.3");
+        assert_eq!(get("b").to_string(), "[false, true, true]");
+        assert!(matches!(get("c"), Value::BigInt(_)));
+        assert_eq!(get("c").to_string(), "9223372036854775808");
+        assert_eq!(get("d").to_string(), "[59.97, 0.25, 2, 1, -0.50]");
+        assert_eq!(
+            get("e").to_string(),
+            "[bigint, decimal, bigint, decimal, number, true]"
+        );
+        assert_eq!(
+            get("f").to_string(),
+            "[12, 0.10, 0.1, 0.1, 99999999999999999999, bigint, 100000000000000000000]"
+        );
+        assert_eq!(
+            get("g").to_string(),
+            "[123456789012345678901234567890, 2, 2]"
+        );
+        assert_eq!(
+            get("h").to_string(),
+            "[3, -3, 3, 1180591620717411303424, 1.21]"
+        );
+        assert_eq!(get("i").to_string(), "[true, true, true, true, true, true]");
+        assert_eq!(
+            get("j").to_string(),
+            "[41152263004115226300411522630, -3, 1, 2.5]"
+        );
+
+        let errors = [
+            ("1n / 0", RuntimeErrorKind::DivisionByZero),
+            ("0.5m % 0m", RuntimeErrorKind::DivisionByZero),
+            ("decimal('abc')", RuntimeErrorKind::InvalidNumber),
+            (
+                "79228162514264337593543950335m * 2",
+                RuntimeErrorKind::InvalidOperation(
+                    "the result doesn't fit in a decimal".to_owned(),
+                ),
+            ),
+            (
+                "1m + 79228162514264337593543950336n",
+                RuntimeErrorKind::InvalidOperation(
+                    "the result doesn't fit in a decimal".to_owned(),
+                ),
+            ),
+            (
+                "int(1n * 9223372036854775808n)",
+                RuntimeErrorKind::InvalidNumber,
+            ),
+            (
+                "bigint(null)",
+                RuntimeErrorKind::TypeExpection("number".to_owned(), "null".to_owned()),
+            ),
+        ];
+
+        for (source, kind) in errors {
+            let error = interpreter.run_source(source).unwrap_err();
+            assert_eq!(error.kind, kind, "error of '{source}'");
+        }
+    }
+
+    #[test]
+    fn test_try() {
+        let source = "
+            set thrown = null
+            try {
+                throw(\"oops\")
+            } catch (err) {
+                thrown = err
+            }
+            set kind = null
+            set message = null
+            set line = null
+            function divide(a, b) {
+                return a / b
+            }
+            try {
+                divide(1, 0)
+            } catch (err) {
+                kind = err.kind
+                message = err.message
+                line = err.line
+            }
+            set i = 0
+            set count = 0
+            while i < 3 {
+                i += 1
+                try {
+                    if i == 2 { continue }
+                    count += 1
+                } catch (err) {}
+            }
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        for node in ast {
+            interpreter.interpret(node);
+        }
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("kind"), Value::String("DivisionByZero".to_owned()));
+        assert_eq!(get("message"), Value::String("division by zero".to_owned()));
+        assert_eq!(get("thrown"), Value::String("oops".to_owned()));
+        assert_eq!(get("line"), Value::Number(12.0));
+        assert_eq!(get("count"), Value::Number(2.0));
+
+        let source = "throw(\"oops\")";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        let error = interpreter.interpret(ast[0].clone()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "uncaught exception: oops (line 1, col 6)"
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let dir = env::temp_dir().join("icelang_test_import");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("counter.ic"),
+            "set count = 0; export set counter = { next: lambda() { count += 1; count } };",
+        )
+        .unwrap();
+        fs::write(dir.join("first.ic"), "import('second')").unwrap();
+        fs::write(dir.join("second.ic"), "import('first.ic')").unwrap();
+
+        let source = "
+            set a = import('counter').counter;
+            set { counter } = import('counter.ic');
+            set b = counter;
+            a.next();
+            set c = b.next();
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(dir.clone());
+        interpreter.load_builtin(get_io_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("c"), Value::Number(2.0));
+
+        let tokens = Lexer::new("import('first')").tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let error = interpreter.interpret_program(&ast).unwrap_err();
+        let first = dir.join("first.ic").canonicalize().unwrap();
+        let second = dir.join("second.ic").canonicalize().unwrap();
+        let chain = [&first, &second, &first]
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+
+        assert_eq!(error.kind, RuntimeErrorKind::CyclicImport(chain));
+
+        let tokens = Lexer::new("set { nope } = import('counter')")
+            .tokenize()
+            .unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let error = interpreter.interpret_program(&ast).unwrap_err();
+        let counter = dir.join("counter.ic").canonicalize().unwrap();
+        let kind =
+            RuntimeErrorKind::UndefinedExport("nope".to_owned(), counter.display().to_string());
+
+        assert_eq!(error.kind, kind);
+        assert_eq!(error.position, lexer::utils::Position::new(0, 6, 0, 9));
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let source = "
+            set name = 'Ice';
+            set items = [1, 'two', null];
+            set a = 'Hello {name}, you have {length(items)} items';
+            set b = \"{items}{true} \\{name\\} {'{name + \"!\"}'}\";
+            set c = '{{ a: 1 }.a / 2}';
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(
+            get("a"),
+            Value::String("Hello Ice, you have 3 items".to_owned())
+        );
+        assert_eq!(
+            get("b"),
+            Value::String("[1, two, null]true {name} Ice!".to_owned())
+        );
+        assert_eq!(get("c"), Value::String("0.5".to_owned()));
+
+        let tokens = Lexer::new("'value: {missing}'").tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let error = interpreter.interpret_program(&ast).unwrap_err();
+
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::UndefinedIdentifier("missing".to_owned())
+        );
+        assert_eq!(error.position.col_start, 9);
+    }
+
+    #[test]
+    fn test_unicode() {
+        let source = "
+            set prénom = 'Zoë \\u{1F976}';
+            set taille = length(prénom);
+            set dernier = prénom[4];
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("taille"), Value::Number(5.0));
+        assert_eq!(get("dernier"), Value::String("🥶".to_owned()));
+    }
+
+    #[test]
+    fn test_embedding() {
+        let interpreter = Interpreter::new(PathBuf::new());
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let log = calls.clone();
+        interpreter.register_function("log", 1, move |_, args| {
+            log.borrow_mut().push(args[0].clone());
+            Ok(Value::Null)
+        });
+        interpreter.register_function("fail", 0, |context, _| {
+            Err(context.error(RuntimeErrorKind::InvalidArg))
+        });
+        interpreter.set_global("limit", Value::Number(3.0));
+
+        let source = "
+            function add(a, b) { a + b }
+            for i in 0 to limit { log(i * 2) }
+            set result = add(limit, 1);
+            set failure = lambda() { fail() };
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        interpreter.interpret_program(&ast).unwrap();
+
+        let expected: Vec<_> = [0.0, 2.0, 4.0].into_iter().map(Value::Number).collect();
+        assert_eq!(*calls.borrow(), expected);
+        assert_eq!(interpreter.get_global("result"), Some(Value::Number(4.0)));
+        assert_eq!(interpreter.get_global("missing"), None);
+
+        let arguments = vec![Value::Number(2.0), Value::Number(5.0)];
+        assert_eq!(interpreter.call("add", arguments), Ok(Value::Number(7.0)));
+
+        let error = interpreter.call("add", vec![]).unwrap_err();
+        assert_eq!(error.kind, RuntimeErrorKind::InvalidArgument(2, 0));
+
+        let error = interpreter.call("failure", vec![]).unwrap_err();
+        assert_eq!(error.kind, RuntimeErrorKind::InvalidArg);
+        assert_eq!(error.position.line_start, 4);
+
+        let error = interpreter.call("log", vec![]).unwrap_err();
+        assert_eq!(error.kind, RuntimeErrorKind::InvalidArgument(1, 0));
+    }
+
+    #[test]
+    fn test_named_exports() {
+        let dir = env::temp_dir().join("icelang_test_named_exports");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("shapes.ic"),
+            "
+            export set sides = 4;
+            export function area(size) { size * size }
+            set hidden = true;
+            sides += 1;
+            ",
+        )
+        .unwrap();
+
+        let source = "
+            set { sides, area } = import('shapes');
+            set a = area(3);
+            set shapes = import('shapes');
+            set b = shapes.hidden;
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(dir);
+        interpreter.load_builtin(get_io_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+        let get = |name| interpreter.environment.as_ref().borrow().get(name).unwrap();
+
+        assert_eq!(get("sides"), Value::Number(5.0));
+        assert_eq!(get("a"), Value::Number(9.0));
+        assert_eq!(get("b"), Value::Null);
+    }
+
+    #[test]
+    fn test_traceback() {
+        let source = "
+function inner(x) {
+    return sqrt(x)
+}
+set outer = lambda(x) {
+    inner(x)
+}
+try { outer('a') } catch (e) { e.kind }
+outer('b')
+";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let path = PathBuf::from("modules/main.ic");
+        let interpreter = Interpreter::new(PathBuf::from("modules"));
+        interpreter.set_file(&path);
+        interpreter.load_builtin(get_std_builtins());
+        let error = interpreter.interpret_program(&ast).unwrap_err();
+        let frames: Vec<_> = error
+            .frames
+            .iter()
+            .map(|frame| (frame.name.as_str(), frame.position.line_start, &frame.path))
+            .collect();
+
+        assert_eq!(
+            frames,
+            [
+                ("sqrt", 2, &path),
+                ("inner", 5, &path),
+                ("anonymous", 8, &path)
+            ]
+        );
+        assert_eq!(
+            error.frames[2].to_string(),
+            "in 'anonymous' called at modules/main.ic, line 9, col 6"
+        );
+        assert_eq!(error.path, Some(path));
+
+        let dir = env::temp_dir().join("icelang_test_traceback");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("numbers.ic"),
+            "function check(x) {\n    1 / x\n}\nexport set divide = lambda(x) check(x);",
+        )
+        .unwrap();
+
+        let source = "set { divide } = import('numbers');\ndivide(0);";
+        let main = dir.join("main.ic");
+        let interpreter = Interpreter::new(dir.clone());
+        interpreter.set_file(&main);
+        interpreter.load_builtin(get_io_builtins());
+        let error = interpreter.run_source(source).unwrap_err();
+        let module = dir.join("numbers.ic").canonicalize().unwrap();
+        let frames: Vec<_> = error
+            .frames
+            .iter()
+            .map(|frame| (frame.name.as_str(), frame.position.line_start, &frame.path))
+            .collect();
+
+        assert_eq!(error.kind, RuntimeErrorKind::DivisionByZero);
+        assert_eq!(error.path, Some(module.clone()));
+        assert_eq!(frames, [("check", 3, &module), ("anonymous", 1, &main)]);
+    }
 }
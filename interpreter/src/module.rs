@@ -0,0 +1,60 @@
+use super::value::Value;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+pub type RefModules = Rc<RefCell<ModuleRegistry>>;
+
+/// Modules imported during a run, keyed by their canonical path. Each module
+/// is only evaluated once and the chain of modules being loaded is used to
+/// detect cycles.
+#[derive(Debug, Default)]
+pub struct ModuleRegistry {
+    exports: HashMap<PathBuf, Value>,
+    loading: Vec<PathBuf>,
+}
+
+impl ModuleRegistry {
+    pub fn get(&self, path: &Path) -> Option<Value> {
+        self.exports.get(path).cloned()
+    }
+
+    /// Marks the module as being loaded, or returns the chain of files that
+    /// lead back to it when it is already loading.
+    pub fn enter(&mut self, path: PathBuf) -> Result<(), Vec<PathBuf>> {
+        if let Some(index) = self.loading.iter().position(|value| *value == path) {
+            let mut chain = self.loading[index..].to_vec();
+            chain.push(path);
+            return Err(chain);
+        }
+
+        self.loading.push(path);
+
+        Ok(())
+    }
+
+    pub fn leave(&mut self) {
+        self.loading.pop();
+    }
+
+    /// Returns the path of the module whose exports are `value`.
+    pub fn path_of(&self, value: &Value) -> Option<&Path> {
+        let Value::Object(object) = value else {
+            return None;
+        };
+
+        self.exports
+            .iter()
+            .find(
+                |(_, exports)| matches!(exports, Value::Object(other) if Rc::ptr_eq(object, other)),
+            )
+            .map(|(path, _)| path.as_path())
+    }
+
+    pub fn insert(&mut self, path: PathBuf, value: Value) {
+        self.exports.insert(path, value);
+    }
+}
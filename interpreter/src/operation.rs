@@ -0,0 +1,351 @@
+use crate::{
+    environment::Environment,
+    error::{RuntimeError, RuntimeErrorKind},
+    module::RefModules,
+    value::{Function, Range, RefVal, Value},
+    Eval,
+};
+
+use lexer::{tokens::TokenType, utils::Position};
+use num_traits::Zero;
+use std::{cell::RefCell, ops, rc::Rc};
+
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        value if value.is_number() => !is_zero(value),
+        Value::Boolean(value) => *value,
+        Value::Null => false,
+        Value::String(value) => !value.is_empty(),
+        Value::Array(value) => !value.borrow().is_empty(),
+        Value::Object(value) => !value.borrow().values.is_empty(),
+        _ => true,
+    }
+}
+
+/// Indexes are integers, floats with a fractional part are rejected rather
+/// than truncated.
+fn get_numerical_index(value: Value, pos: Position) -> Result<usize, RuntimeError> {
+    match value.as_integer().map(usize::try_from) {
+        Some(Ok(index)) => Ok(index),
+        _ => Err(RuntimeError::new(RuntimeErrorKind::InvalidIndex, pos)),
+    }
+}
+
+fn get_key(value: Value, pos: Position) -> Result<String, RuntimeError> {
+    match value {
+        Value::Number(value) => Ok(value.to_string()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::BigInt(value) => Ok(value.to_string()),
+        Value::Decimal(value) => Ok(value.normalize().to_string()),
+        Value::String(value) => Ok(value),
+        _ => Err(RuntimeError::new(RuntimeErrorKind::InvalidIndex, pos)),
+    }
+}
+
+pub fn unary_operation(
+    operator: &TokenType,
+    operand: Value,
+    pos: Position,
+) -> Result<Value, RuntimeError> {
+    match operator {
+        TokenType::Minus => match operand {
+            Value::Number(value) => Ok(Value::Number(-value)),
+            Value::Integer(value) => match value.checked_neg() {
+                Some(value) => Ok(Value::Integer(value)),
+                None => Ok(Value::Number(-(value as f64))),
+            },
+            Value::BigInt(value) => Ok(Value::BigInt(-value)),
+            Value::Decimal(value) => Ok(Value::Decimal(-value)),
+            _ => Err(RuntimeError::new(
+                RuntimeErrorKind::TypeExpection("number".to_string(), operand.get_type()),
+                pos,
+            )),
+        },
+        TokenType::Bang => Ok(Value::Boolean(!is_truthy(&operand))),
+        _ => unreachable!(),
+    }
+}
+
+fn is_zero(value: &Value) -> bool {
+    match value {
+        Value::Number(value) => *value == 0.0,
+        Value::Integer(value) => *value == 0,
+        Value::BigInt(value) => value.is_zero(),
+        Value::Decimal(value) => value.is_zero(),
+        _ => false,
+    }
+}
+
+/// Logical operators short-circuit so they are handled by the evaluators.
+pub fn binary_operation(
+    operator: &TokenType,
+    left: Value,
+    right: Value,
+    pos: Position,
+) -> Result<Value, RuntimeError> {
+    let left_type = left.get_type();
+    let right_type = right.get_type();
+    let numbers = left.is_number() && right.is_number();
+
+    // Numbers only fail to combine when the result overflows a decimal.
+    let invalid = |action: &str| {
+        let message = match numbers {
+            true => "the result doesn't fit in a decimal".to_owned(),
+            false => format!("cannot {} a '{}' by a '{}'", action, left_type, right_type),
+        };
+
+        RuntimeError::new(RuntimeErrorKind::InvalidOperation(message), pos)
+    };
+
+    match operator {
+        TokenType::Asterix => (left * right).ok_or_else(|| invalid("multiply")),
+        TokenType::Slash => {
+            if is_zero(&right) {
+                return Err(RuntimeError::new(RuntimeErrorKind::DivisionByZero, pos));
+            }
+
+            (left / right).ok_or_else(|| invalid("divide"))
+        }
+        TokenType::Minus => (left - right).ok_or_else(|| invalid("substract")),
+        TokenType::Plus => (left + right).ok_or_else(|| invalid("add")),
+        TokenType::Modulo => {
+            if is_zero(&right) {
+                return Err(RuntimeError::new(RuntimeErrorKind::DivisionByZero, pos));
+            }
+
+            (left % right).ok_or_else(|| invalid("divide"))
+        }
+        TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
+            compare(operator, left, right, pos)
+        }
+        TokenType::EqualEqual => Ok(Value::Boolean(left == right)),
+        TokenType::BangEqual => Ok(Value::Boolean(left != right)),
+        TokenType::To => match (left, right) {
+            (start, end) if start.is_number() && end.is_number() => {
+                match (start.as_integer(), end.as_integer()) {
+                    (Some(start), Some(end)) => {
+                        let range = Range::NumberRange(ops::Range { start, end });
+                        Ok(Value::Range(range))
+                    }
+                    _ => Err(RuntimeError::new(RuntimeErrorKind::InvalidRange, pos)),
+                }
+            }
+            (Value::String(start), Value::String(end)) => {
+                if start.len() == 1 && end.len() == 1 {
+                    let start = start.chars().next().unwrap();
+                    let end = end.chars().next().unwrap();
+                    let range = Range::CharRange(ops::Range { start, end });
+                    Ok(Value::Range(range))
+                } else {
+                    Err(RuntimeError::new(RuntimeErrorKind::InvalidRange, pos))
+                }
+            }
+            _ => Err(RuntimeError::new(RuntimeErrorKind::InvalidRange, pos)),
+        },
+        _ => unreachable!(),
+    }
+}
+
+/// Orders numbers or strings, other values can't be ordered.
+fn compare(
+    operator: &TokenType,
+    left: Value,
+    right: Value,
+    pos: Position,
+) -> Result<Value, RuntimeError> {
+    if !left.is_comparable_with(&right) {
+        let (expected, found) = match &left {
+            Value::String(_) => ("string", right.get_type()),
+            value if value.is_number() => ("number", right.get_type()),
+            value => ("number or string", value.get_type()),
+        };
+
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::TypeExpection(expected.to_owned(), found),
+            pos,
+        ));
+    }
+
+    let result = match operator {
+        TokenType::Greater => left > right,
+        TokenType::GreaterEqual => left >= right,
+        TokenType::Less => left < right,
+        TokenType::LessEqual => left <= right,
+        _ => unreachable!(),
+    };
+
+    Ok(Value::Boolean(result))
+}
+
+pub fn assign_operation(
+    target: &RefVal,
+    operator: &TokenType,
+    value: Value,
+    pos: Position,
+) -> Result<Value, RuntimeError> {
+    if let TokenType::Equal = operator {
+        *target.borrow_mut() = value.clone();
+        return Ok(value);
+    }
+
+    let prev = target.borrow().clone();
+    let result = match operator {
+        TokenType::PlusEqual => prev + value,
+        TokenType::MinusEqaul => prev - value,
+        TokenType::AsterixEqual => prev * value,
+        TokenType::SlashEqual => prev / value,
+        TokenType::ModuloEqual => prev % value,
+        _ => unreachable!(),
+    };
+
+    match result {
+        Some(result) => {
+            *target.borrow_mut() = result.clone();
+            Ok(result)
+        }
+        None => Err(RuntimeError::new(RuntimeErrorKind::InvalidAssignment, pos)),
+    }
+}
+
+pub fn get_index(value: Value, index: Value, pos: Position) -> Result<Value, RuntimeError> {
+    if let Value::Object(object) = value {
+        let key = get_key(index, pos)?;
+        let value = match object.borrow().values.get(&key) {
+            Some(value) => value.borrow().clone(),
+            None => Value::Null,
+        };
+
+        return Ok(value);
+    }
+
+    let index = get_numerical_index(index, pos)?;
+
+    match value {
+        Value::Array(array) => match array.borrow().get(index) {
+            Some(value) => Ok(value.borrow().clone()),
+            None => Ok(Value::Null),
+        },
+        Value::String(string) => match string.chars().nth(index) {
+            Some(value) => Ok(Value::String(value.to_string())),
+            None => Ok(Value::Null),
+        },
+        _ => Err(RuntimeError::new(RuntimeErrorKind::UnindexableType, pos)),
+    }
+}
+
+/// Returns a reference to the indexed slot, growing arrays and inserting
+/// missing object keys so that the slot can be assigned.
+pub fn get_index_ref(value: &Value, index: Value, pos: Position) -> Result<RefVal, RuntimeError> {
+    match value {
+        Value::Array(array) => {
+            let index = get_numerical_index(index, pos)?;
+            let mut array = array.borrow_mut();
+            if index >= array.len() {
+                array.resize_with(index + 1, || Rc::new(RefCell::new(Value::Null)))
+            }
+
+            Ok(array[index].clone())
+        }
+        Value::Object(object) => {
+            let key = get_key(index, pos)?;
+            let rf = object
+                .borrow_mut()
+                .values
+                .entry(key)
+                .or_insert_with(|| Rc::new(RefCell::new(Value::Null)))
+                .clone();
+
+            Ok(rf)
+        }
+        _ => Err(RuntimeError::new(RuntimeErrorKind::InvalidAssignment, pos)),
+    }
+}
+
+pub fn get_property(value: Value, prop: &str, pos: Position) -> Result<Value, RuntimeError> {
+    match value {
+        Value::Object(object) => match object.borrow().values.get(prop) {
+            Some(value) => Ok(value.borrow().clone()),
+            None => Ok(Value::Null),
+        },
+        _ => Err(RuntimeError::new(RuntimeErrorKind::NotAnObject, pos)),
+    }
+}
+
+/// Reads a destructured member. Unlike a property access, a name that an
+/// imported module doesn't export is an error rather than null.
+pub fn get_member(
+    value: Value,
+    name: &str,
+    modules: &RefModules,
+    pos: Position,
+) -> Result<Value, RuntimeError> {
+    if let Value::Object(object) = &value {
+        if !object.borrow().values.contains_key(name) {
+            if let Some(path) = modules.borrow().path_of(&value) {
+                let kind =
+                    RuntimeErrorKind::UndefinedExport(name.to_owned(), path.display().to_string());
+                return Err(RuntimeError::new(kind, pos));
+            }
+        }
+    }
+
+    get_property(value, name, pos)
+}
+
+pub fn get_property_ref(value: &Value, prop: &str, pos: Position) -> Result<RefVal, RuntimeError> {
+    match value {
+        Value::Object(object) => {
+            let rf = object
+                .borrow_mut()
+                .values
+                .entry(prop.to_owned())
+                .or_insert_with(|| Rc::new(RefCell::new(Value::Null)))
+                .clone();
+
+            Ok(rf)
+        }
+        _ => Err(RuntimeError::new(RuntimeErrorKind::NotAnObject, pos)),
+    }
+}
+
+pub fn call_function(
+    function: &Function,
+    this: Option<Value>,
+    arguments: Vec<Value>,
+    pos: Position,
+) -> Result<Value, RuntimeError> {
+    // compiled functions are called by the backend that compiled them
+    let Function::Declared(function) = function else {
+        return Err(RuntimeError::new(RuntimeErrorKind::NotFunciton, pos));
+    };
+    let expected = function.declaration.parameter.len();
+    let got = arguments.len();
+
+    if expected != got {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::InvalidArgument(expected, got),
+            pos,
+        ));
+    }
+
+    let new_env = Rc::new(RefCell::new(Environment::from(function.closure.clone())));
+
+    if let Some(this) = this {
+        new_env.borrow_mut().set("self", this);
+    }
+
+    for (param, value) in function.declaration.parameter.iter().zip(arguments) {
+        new_env.borrow_mut().set(&param.lexeme, value);
+    }
+
+    let completion = function
+        .declaration
+        .body
+        .evaluate(&new_env)
+        .map_err(|error| error.in_file(function.closure.borrow().get_file()))?;
+
+    match completion.into_value() {
+        Some(value) => Ok(value),
+        None => Ok(Value::Null),
+    }
+}
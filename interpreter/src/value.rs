@@ -1,9 +1,12 @@
-use crate::builtin::Builtin;
+use crate::{builtin::Builtin, environment::RefEnv};
 
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
 use parser::ast::FunctionDeclaration;
+use rust_decimal::Decimal;
 
 use std::{
-    borrow::Borrow,
+    any::Any,
     cell::RefCell,
     collections::HashMap,
     fmt,
@@ -12,84 +15,204 @@ use std::{
 };
 
 pub type RefVal = Rc<RefCell<Value>>;
+pub type RefArray = Rc<RefCell<Vec<RefVal>>>;
+pub type RefObject = Rc<RefCell<Object>>;
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Debug, Clone)]
 pub enum Value {
+    /// Floating point number
     Number(f64),
+    /// Integer, arithmetic that overflows it gives a floating point number
+    Integer(i64),
+    /// Integer of any size
+    BigInt(BigInt),
+    /// Exact decimal number with up to 28 significant digits
+    Decimal(Decimal),
     String(String),
     Boolean(bool),
     Null,
-    Array(Vec<RefVal>),
-    Object(Object),
+    /// Arrays and objects are references, copying the value shares its
+    /// items
+    Array(RefArray),
+    Object(RefObject),
     Function(Function),
     Builtin(Builtin),
     Range(Range),
 }
 
-#[derive(Debug, Clone)]
-pub struct Function {
-    pub declaration: FunctionDeclaration,
+/// A function declared in icelang code. The tree-walking interpreter
+/// evaluates its declaration, other backends run their own compiled code.
+#[derive(Clone, PartialEq)]
+pub enum Function {
+    Declared(Declared),
+    Compiled(Compiled),
 }
 
-impl PartialEq for Function {
-    fn eq(&self, other: &Self) -> bool {
-        self.declaration.token == other.declaration.token
+impl Function {
+    pub fn new(declaration: FunctionDeclaration, closure: RefEnv) -> Self {
+        Function::Declared(Declared {
+            declaration: Rc::new(declaration),
+            closure,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Function::Declared(function) => function.get_name(),
+            Function::Compiled(function) => function.get_name(),
+        }
     }
 }
 
-impl PartialOrd for Function {
-    fn partial_cmp(&self, _other: &Self) -> Option<std::cmp::Ordering> {
-        Some(std::cmp::Ordering::Less)
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Function::Declared(function) => function.fmt(f),
+            Function::Compiled(function) => function.fmt(f),
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Object {
-    pub values: HashMap<String, RefVal>,
+#[derive(Clone)]
+pub struct Declared {
+    pub declaration: Rc<FunctionDeclaration>,
+    pub closure: RefEnv,
 }
 
-impl PartialOrd for Object {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self.values.len() > other.values.len() {
-            Some(std::cmp::Ordering::Greater)
-        } else {
-            Some(std::cmp::Ordering::Less)
+impl Declared {
+    pub fn get_name(&self) -> &str {
+        match &self.declaration.token {
+            Some(token) => &token.lexeme,
+            None => "anonymous",
         }
     }
 }
 
+impl fmt::Debug for Declared {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the captured environment may contain the function itself
+        f.debug_struct("Function")
+            .field("declaration", &self.declaration)
+            .field("closure", &"<environment>")
+            .finish()
+    }
+}
+
+/// Functions are equal when they are the same function value, two lambdas
+/// with the same body are different functions.
+impl PartialEq for Declared {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.declaration, &other.declaration)
+            && Rc::ptr_eq(&self.closure, &other.closure)
+    }
+}
+
+/// A function compiled by another backend such as the bytecode VM. Its code
+/// is owned by that backend, which is the only one able to call it.
+#[derive(Clone)]
+pub struct Compiled {
+    pub name: Option<String>,
+    pub inner: Rc<dyn Any>,
+}
+
+impl Compiled {
+    pub fn get_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("anonymous")
+    }
+}
+
+impl fmt::Debug for Compiled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compiled")
+            .field("name", &self.name)
+            .field("inner", &"<compiled function>")
+            .finish()
+    }
+}
+
+impl PartialEq for Compiled {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Object {
+    pub values: HashMap<String, RefVal>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Range {
     NumberRange(ops::Range<i64>),
     CharRange(ops::Range<char>),
 }
 
-impl PartialOrd for Range {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+/// Numbers are equal when their numerical values are, whatever their kinds.
+/// Arrays and objects are equal when their items are, whether or not they
+/// are the same reference, and functions only when they are the same
+/// function. Values of different kinds are never equal.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Range::NumberRange(a), Range::NumberRange(b)) => {
-                if a.end > b.end {
-                    Some(std::cmp::Ordering::Greater)
-                } else {
-                    Some(std::cmp::Ordering::Less)
-                }
+            (a, b) if a.is_number() && b.is_number() => {
+                a.partial_cmp(b) == Some(std::cmp::Ordering::Equal)
             }
-            (Range::CharRange(a), Range::CharRange(b)) => {
-                if a.end > b.end {
-                    Some(std::cmp::Ordering::Greater)
-                } else {
-                    Some(std::cmp::Ordering::Less)
-                }
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Null, Value::Null) => true,
+            (Value::Array(a), Value::Array(b)) => {
+                Rc::ptr_eq(a, b) || compare_containers(self, other, || *a.borrow() == *b.borrow())
             }
+            (Value::Object(a), Value::Object(b)) => {
+                Rc::ptr_eq(a, b) || compare_containers(self, other, || *a.borrow() == *b.borrow())
+            }
+            (Value::Function(a), Value::Function(b)) => a == b,
+            (Value::Builtin(a), Value::Builtin(b)) => a == b,
+            (Value::Range(a), Value::Range(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Only numbers and strings are ordered: numbers by their numerical value
+/// once promoted to the same kind and strings lexicographically. Any other
+/// pair of values is unordered, which the comparison operators report as an
+/// error.
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if let Some(operands) = Operands::promote(self, other) {
+            return match operands {
+                Operands::Integer(a, b) => a.partial_cmp(&b),
+                Operands::BigInt(a, b) => a.partial_cmp(&b),
+                Operands::Decimal(a, b) => a.partial_cmp(&b),
+                Operands::Float(a, b) => a.partial_cmp(&b),
+            };
+        }
+
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => a.partial_cmp(b),
             _ => None,
         }
     }
 }
 
 impl Value {
+    /// Whether the value can be compared with `other` by the comparison
+    /// operators, a float that is NaN is unordered but still comparable.
+    pub fn is_comparable_with(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::String(_), Value::String(_)) => true,
+            (a, b) => a.is_number() && b.is_number(),
+        }
+    }
+
+    /// Integers and floats are both reported as `number`, the big integer and
+    /// decimal kinds have their own types.
     pub fn get_type(&self) -> String {
         let value_type = match self {
-            Value::Number(_) => "number",
+            Value::Number(_) | Value::Integer(_) => "number",
+            Value::BigInt(_) => "bigint",
+            Value::Decimal(_) => "decimal",
             Value::String(_) => "string",
             Value::Boolean(_) => "boolean",
             Value::Null => "null",
@@ -103,7 +226,139 @@ impl Value {
     }
 
     pub fn is_number(&self) -> bool {
-        matches!(self, Value::Number(_))
+        matches!(
+            self,
+            Value::Number(_) | Value::Integer(_) | Value::BigInt(_) | Value::Decimal(_)
+        )
+    }
+
+    /// The value as an integer, other numbers are only converted when they
+    /// have no fractional part and fit in an integer.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            Value::Number(value) => float_to_integer(*value),
+            Value::BigInt(value) => value.to_i64(),
+            Value::Decimal(value) if value.fract().is_zero() => value.to_i64(),
+            _ => None,
+        }
+    }
+
+    /// The value as a big integer, other numbers are only converted when they
+    /// have no fractional part.
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        match self {
+            Value::Integer(value) => Some(BigInt::from(*value)),
+            Value::BigInt(value) => Some(value.clone()),
+            Value::Number(value) if value.fract() == 0.0 => BigInt::from_f64(*value),
+            Value::Decimal(value) if value.fract().is_zero() => BigInt::from_i128(value.to_i128()?),
+            _ => None,
+        }
+    }
+
+    /// The value as a decimal, big integers must fit in a decimal and floats
+    /// are converted to the shortest decimal that reads back as them.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Integer(value) => Some(Decimal::from(*value)),
+            Value::BigInt(value) => Decimal::try_from_i128_with_scale(value.to_i128()?, 0).ok(),
+            Value::Decimal(value) => Some(*value),
+            Value::Number(value) => Decimal::from_f64(*value),
+            _ => None,
+        }
+    }
+
+    /// The value as a float, numbers too precise to be represented exactly
+    /// are rounded.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Number(value) => Some(*value),
+            Value::Integer(value) => Some(*value as f64),
+            Value::BigInt(value) => value.to_f64(),
+            Value::Decimal(value) => value.to_f64(),
+            _ => None,
+        }
+    }
+
+    pub fn new_array(items: Vec<RefVal>) -> Value {
+        Value::Array(Rc::new(RefCell::new(items)))
+    }
+
+    pub fn new_object(values: HashMap<String, RefVal>) -> Value {
+        Value::Object(Rc::new(RefCell::new(Object { values })))
+    }
+
+    /// Copies an array or an object into a new container holding the same
+    /// items, other values are returned as is.
+    pub fn shallow_clone(&self) -> Value {
+        let copy = |value: &RefVal| Rc::new(RefCell::new(value.borrow().clone()));
+
+        match self {
+            Value::Array(array) => Value::new_array(array.borrow().iter().map(copy).collect()),
+            Value::Object(object) => Value::new_object(
+                object
+                    .borrow()
+                    .values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), copy(value)))
+                    .collect(),
+            ),
+            value => value.clone(),
+        }
+    }
+
+    /// Copies the value along with every array and object it contains. A
+    /// container referenced several times is copied once, so the copy has
+    /// the same shape, cycles included.
+    pub fn deep_clone(&self) -> Value {
+        self.deep_clone_with(&mut HashMap::new())
+    }
+
+    fn deep_clone_with(&self, copies: &mut HashMap<usize, Value>) -> Value {
+        let address = match self {
+            Value::Array(array) => Rc::as_ptr(array) as usize,
+            Value::Object(object) => Rc::as_ptr(object) as usize,
+            value => return value.clone(),
+        };
+
+        if let Some(copy) = copies.get(&address) {
+            return copy.clone();
+        }
+
+        let copy_item = |value: &RefVal, copies: &mut HashMap<usize, Value>| {
+            Rc::new(RefCell::new(value.borrow().deep_clone_with(copies)))
+        };
+
+        match self {
+            Value::Array(array) => {
+                let copy = Rc::new(RefCell::new(Vec::new()));
+                copies.insert(address, Value::Array(copy.clone()));
+                let items = array
+                    .borrow()
+                    .iter()
+                    .map(|item| copy_item(item, copies))
+                    .collect();
+                *copy.borrow_mut() = items;
+
+                Value::Array(copy)
+            }
+            Value::Object(object) => {
+                let copy = Rc::new(RefCell::new(Object {
+                    values: HashMap::new(),
+                }));
+                copies.insert(address, Value::Object(copy.clone()));
+                let values = object
+                    .borrow()
+                    .values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), copy_item(value, copies)))
+                    .collect();
+                copy.borrow_mut().values = values;
+
+                Value::Object(copy)
+            }
+            _ => unreachable!(),
+        }
     }
 
     pub fn is_iterable(&self) -> bool {
@@ -113,44 +368,64 @@ impl Value {
                 | Value::Array(_)
                 | Value::Object(_)
                 | Value::Range(_)
-                | Value::Number(_)
+                | Value::Integer(_)
         )
     }
 
+    /// Iterates over the items of the value, arrays and objects are iterated
+    /// as they were when the iteration started.
     pub fn iter(&self) -> Box<dyn Iterator<Item = (Value, Value)> + '_> {
         match self {
-            Value::Number(value) => {
-                let range = 0..*value as i64;
+            Value::Integer(value) => {
+                let range = 0..*value;
                 Box::new(
-                    range.into_iter().enumerate().map(|(key, value)| {
-                        (Value::Number(key as f64), Value::Number(value as f64))
-                    }),
+                    range
+                        .into_iter()
+                        .enumerate()
+                        .map(|(key, value)| (Value::Integer(key as i64), Value::Integer(value))),
                 )
             }
             Value::Range(range) => match range {
-                Range::NumberRange(value) => {
-                    Box::new(value.clone().enumerate().map(|(key, value)| {
-                        (Value::Number(key as f64), Value::Number(value as f64))
-                    }))
-                }
+                Range::NumberRange(value) => Box::new(
+                    value
+                        .clone()
+                        .enumerate()
+                        .map(|(key, value)| (Value::Integer(key as i64), Value::Integer(value))),
+                ),
                 Range::CharRange(value) => {
                     Box::new(value.clone().enumerate().map(|(key, value)| {
-                        (Value::Number(key as f64), Value::String(value.into()))
+                        (Value::Integer(key as i64), Value::String(value.into()))
                     }))
                 }
             },
             Value::String(string) => {
                 Box::new(string.chars().enumerate().map(|(index, value)| {
-                    (Value::Number(index as f64), Value::String(value.into()))
+                    (Value::Integer(index as i64), Value::String(value.into()))
                 }))
             }
-            Value::Array(array) => Box::new(array.iter().enumerate().map(|(index, value)| {
-                (Value::Number(index as f64), value.as_ref().borrow().clone())
-            })),
+            Value::Array(array) => {
+                let items: Vec<_> = array
+                    .borrow()
+                    .iter()
+                    .map(|item| item.borrow().clone())
+                    .collect();
+
+                Box::new(
+                    items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, value)| (Value::Integer(index as i64), value)),
+                )
+            }
             Value::Object(object) => {
-                Box::new(object.values.borrow().iter().map(|(key, value)| {
-                    (Value::String(key.clone()), value.as_ref().borrow().clone())
-                }))
+                let entries: Vec<_> = object
+                    .borrow()
+                    .values
+                    .iter()
+                    .map(|(key, value)| (Value::String(key.clone()), value.borrow().clone()))
+                    .collect();
+
+                Box::new(entries.into_iter())
             }
             _ => Box::new(std::iter::empty()),
         }
@@ -169,47 +444,61 @@ impl fmt::Display for Value {
 
                 write!(f, "{s}")
             }
+            Value::Integer(value) => write!(f, "{value}"),
+            Value::BigInt(value) => write!(f, "{value}"),
+            Value::Decimal(value) => write!(f, "{value}"),
             Value::String(value) => write!(f, "{}", value),
             Value::Boolean(value) => write!(f, "{:?}", value),
             Value::Null => write!(f, "null"),
             Value::Array(items) => {
-                let mut s = String::new();
-                let mut iter = items.iter();
-                if let Some(item) = iter.next() {
-                    s.push_str(&format!("{}", item.as_ref().borrow()));
-                    for item in iter {
-                        s.push_str(&format!(", {}", item.as_ref().borrow()));
+                let s = visit_container(self, || {
+                    let mut s = String::new();
+                    let items = items.borrow();
+                    let mut iter = items.iter();
+                    if let Some(item) = iter.next() {
+                        s.push_str(&format!("{}", item.borrow()));
+                        for item in iter {
+                            s.push_str(&format!(", {}", item.borrow()));
+                        }
                     }
+                    s
+                });
+
+                match s {
+                    Some(s) => write!(f, "[{}]", s),
+                    None => write!(f, "[...]"),
                 }
-                write!(f, "[{}]", s)
             }
             Value::Function(function) => {
-                let name = match &function.declaration.token {
-                    Some(token) => &token.lexeme,
-                    None => "anonymous",
-                };
-                write!(f, "[Function {}]", name)
+                write!(f, "[Function {}]", function.get_name())
             }
             Value::Builtin(builtin) => {
                 write!(f, "[Function {}]", builtin.name)
             }
             Value::Object(object) => {
-                let mut s = String::new();
-                let mut iter = object.values.iter();
-                if let Some((key, value)) = iter.next() {
-                    s.push_str(&format!("{}: {}", key, value.as_ref().borrow()));
-                    for (key, value) in iter {
-                        s.push_str(&format!(", {}: {}", key, value.as_ref().borrow()));
+                let s = visit_container(self, || {
+                    let mut s = String::new();
+                    let object = object.borrow();
+                    let mut iter = object.values.iter();
+                    if let Some((key, value)) = iter.next() {
+                        s.push_str(&format!("{}: {}", key, value.borrow()));
+                        for (key, value) in iter {
+                            s.push_str(&format!(", {}: {}", key, value.borrow()));
+                        }
                     }
+                    s
+                });
+
+                match s {
+                    Some(s) => write!(f, "{{ {} }}", s),
+                    None => write!(f, "{{...}}"),
                 }
-                write!(f, "{{ {} }}", s)
             }
             Value::Range(range) => {
                 let (start, end) = match range {
-                    Range::NumberRange(value) => (
-                        Value::Number(value.start as f64),
-                        Value::Number(value.end as f64),
-                    ),
+                    Range::NumberRange(value) => {
+                        (Value::Integer(value.start), Value::Integer(value.end))
+                    }
                     Range::CharRange(value) => (
                         Value::String(value.start.to_string()),
                         Value::String(value.end.to_string()),
@@ -221,25 +510,166 @@ impl fmt::Display for Value {
     }
 }
 
+thread_local! {
+    /// Addresses of the arrays and objects being visited.
+    static VISITING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+    /// Address pairs of the arrays and objects being compared.
+    static COMPARING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn container_address(value: &Value) -> Option<usize> {
+    match value {
+        Value::Array(array) => Some(Rc::as_ptr(array) as usize),
+        Value::Object(object) => Some(Rc::as_ptr(object) as usize),
+        _ => None,
+    }
+}
+
+/// Runs `visit` on an array or an object, unless it is already being visited
+/// because it contains itself. Walking nested values through this doesn't
+/// loop forever on cycles.
+pub fn visit_container<T>(value: &Value, visit: impl FnOnce() -> T) -> Option<T> {
+    let Some(address) = container_address(value) else {
+        return Some(visit());
+    };
+
+    if VISITING.with(|visiting| visiting.borrow().contains(&address)) {
+        return None;
+    }
+
+    VISITING.with(|visiting| visiting.borrow_mut().push(address));
+    let result = visit();
+    VISITING.with(|visiting| visiting.borrow_mut().pop());
+
+    Some(result)
+}
+
+/// Runs `compare` on two arrays or objects, unless this same pair is already
+/// being compared further up because both contain themselves. Such a pair is
+/// assumed equal, any difference is found by the comparison in progress.
+fn compare_containers(left: &Value, right: &Value, compare: impl FnOnce() -> bool) -> bool {
+    let (Some(left), Some(right)) = (container_address(left), container_address(right)) else {
+        return compare();
+    };
+    let pair = (left, right);
+
+    if COMPARING.with(|comparing| comparing.borrow().contains(&pair)) {
+        return true;
+    }
+
+    COMPARING.with(|comparing| comparing.borrow_mut().push(pair));
+    let result = compare();
+    COMPARING.with(|comparing| comparing.borrow_mut().pop());
+
+    result
+}
+
+/// Converts a float without fractional part to an integer.
+pub fn float_to_integer(value: f64) -> Option<i64> {
+    // i64::MAX as f64 rounds up to 2^63 which is out of range
+    if value.fract() == 0.0 && value >= i64::MIN as f64 && value < i64::MAX as f64 {
+        Some(value as i64)
+    } else {
+        None
+    }
+}
+
+/// Two numbers converted to the kind they are operated in. Numbers are
+/// promoted along integer, big integer, decimal and float, so that the result
+/// is exact unless a float is involved. Big integers too large for a decimal
+/// are mixed with decimals as floats.
+enum Operands {
+    Integer(i64, i64),
+    BigInt(BigInt, BigInt),
+    Decimal(Decimal, Decimal),
+    Float(f64, f64),
+}
+
+impl Operands {
+    fn promote(left: &Value, right: &Value) -> Option<Operands> {
+        let rank = |value: &Value| match value {
+            Value::Integer(_) => Some(0),
+            Value::BigInt(_) => Some(1),
+            Value::Decimal(_) => Some(2),
+            Value::Number(_) => Some(3),
+            _ => None,
+        };
+
+        let operands = match rank(left)?.max(rank(right)?) {
+            0 => Operands::Integer(left.as_integer()?, right.as_integer()?),
+            1 => Operands::BigInt(left.as_bigint()?, right.as_bigint()?),
+            2 => match (left.as_decimal(), right.as_decimal()) {
+                (Some(a), Some(b)) => Operands::Decimal(a, b),
+                _ => Operands::Float(left.as_float()?, right.as_float()?),
+            },
+            _ => Operands::Float(left.as_float()?, right.as_float()?),
+        };
+
+        Some(operands)
+    }
+}
+
+/// An arithmetic operator for each kind of number, the exact kinds give no
+/// result when it doesn't fit in the kind.
+struct Arithmetic {
+    integer: fn(i64, i64) -> Option<i64>,
+    bigint: fn(BigInt, BigInt) -> BigInt,
+    decimal: fn(Decimal, Decimal) -> Option<Decimal>,
+    float: fn(f64, f64) -> f64,
+}
+
+/// Applies an arithmetic operator to two numbers of the kind they are promoted
+/// to. Integers that don't fit in 64 bits are computed on floats, the other
+/// exact kinds never become floats unless one of the operands is a float, so
+/// there is no result when a decimal overflows.
+fn arithmetic(left: Value, right: Value, operator: Arithmetic) -> Option<Value> {
+    let has_float = matches!(left, Value::Number(_)) || matches!(right, Value::Number(_));
+
+    match Operands::promote(&left, &right)? {
+        Operands::Integer(a, b) => match (operator.integer)(a, b) {
+            Some(value) => Some(Value::Integer(value)),
+            None => Some(Value::Number((operator.float)(a as f64, b as f64))),
+        },
+        Operands::BigInt(a, b) => Some(Value::BigInt((operator.bigint)(a, b))),
+        Operands::Decimal(a, b) => (operator.decimal)(a, b).map(Value::Decimal),
+        Operands::Float(a, b) if has_float => Some(Value::Number((operator.float)(a, b))),
+        Operands::Float(_, _) => None,
+    }
+}
+
 impl Mul for Value {
     type Output = Option<Value>;
 
     fn mul(self, rhs: Value) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(a), Value::Number(b)) => Some(Value::Number(a * b)),
-            _ => None,
-        }
+        let operator = Arithmetic {
+            integer: i64::checked_mul,
+            bigint: |a, b| a * b,
+            decimal: |a, b| a.checked_mul(b),
+            float: |a, b| a * b,
+        };
+
+        arithmetic(self, rhs, operator)
     }
 }
 
 impl Div for Value {
     type Output = Option<Value>;
 
+    /// Dividing integers gives an integer only when the division is exact,
+    /// big integers are divided rounding toward zero and decimals are rounded
+    /// to the digits they can hold.
     fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(a), Value::Number(b)) => Some(Value::Number(a / b)),
-            _ => None,
-        }
+        let operator = Arithmetic {
+            integer: |a, b| match a.checked_rem(b) {
+                Some(0) => a.checked_div(b),
+                _ => None,
+            },
+            bigint: |a, b| a / b,
+            decimal: |a, b| a.checked_div(b),
+            float: |a, b| a / b,
+        };
+
+        arithmetic(self, rhs, operator)
     }
 }
 
@@ -247,10 +677,14 @@ impl Sub for Value {
     type Output = Option<Value>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(a), Value::Number(b)) => Some(Value::Number(a - b)),
-            _ => None,
-        }
+        let operator = Arithmetic {
+            integer: i64::checked_sub,
+            bigint: |a, b| a - b,
+            decimal: |a, b| a.checked_sub(b),
+            float: |a, b| a - b,
+        };
+
+        arithmetic(self, rhs, operator)
     }
 }
 
@@ -259,11 +693,19 @@ impl Add for Value {
 
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Value::Number(a), Value::Number(b)) => Some(Value::Number(a + b)),
-            (Value::String(a), Value::Number(b)) => Some(Value::String(format!("{}{}", a, b))),
-            (Value::Number(a), Value::String(b)) => Some(Value::String(format!("{}{}", a, b))),
             (Value::String(a), Value::String(b)) => Some(Value::String(format!("{}{}", a, b))),
-            _ => None,
+            (Value::String(a), b) if b.is_number() => Some(Value::String(format!("{}{}", a, b))),
+            (a, Value::String(b)) if a.is_number() => Some(Value::String(format!("{}{}", a, b))),
+            (a, b) => {
+                let operator = Arithmetic {
+                    integer: i64::checked_add,
+                    bigint: |a, b| a + b,
+                    decimal: |a, b| a.checked_add(b),
+                    float: |a, b| a + b,
+                };
+
+                arithmetic(a, b, operator)
+            }
         }
     }
 }
@@ -271,10 +713,16 @@ impl Add for Value {
 impl Rem for Value {
     type Output = Option<Value>;
 
+    /// The remainder has the sign of the dividend, like the remainder of
+    /// floats.
     fn rem(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(a), Value::Number(b)) => Some(Value::Number(a % b)),
-            _ => None,
-        }
+        let operator = Arithmetic {
+            integer: i64::checked_rem,
+            bigint: |a, b| a % b,
+            decimal: |a, b| a.checked_rem(b),
+            float: |a, b| a % b,
+        };
+
+        arithmetic(self, rhs, operator)
     }
 }
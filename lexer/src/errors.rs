@@ -27,8 +27,30 @@ pub enum LexicalErrorKind {
     UnexpectedCharacter(String),
     #[error("trailing quote {0}")]
     TrailingQuote(char),
+    #[error("unterminated multi-line string")]
+    UnterminatedString,
     #[error("invalid escape character '{0}'")]
     InvalidEscapeChar(String),
+    #[error("invalid unicode escape '{0}'")]
+    InvalidUnicodeEscape(String),
     #[error("invalid foating number '{0}'")]
     InvalidFloat(String),
+    #[error("decimal '{0}' has too many digits")]
+    InvalidDecimal(String),
+    #[error("a big integer can't have a fractional part or an exponent")]
+    FractionalBigInt,
+    #[error("invalid digit '{0}' in a base {1} number")]
+    InvalidDigit(char, u32),
+    #[error("missing digits after '{0}'")]
+    MissingDigits(String),
+    #[error("a number can't have more than one decimal point")]
+    ExtraDecimalPoint,
+    #[error("digit separator '_' must be between two digits")]
+    InvalidSeparator,
+    #[error("unterminated block comment")]
+    UnterminatedComment,
+    #[error("unterminated string interpolation")]
+    UnterminatedInterpolation,
+    #[error("empty string interpolation")]
+    EmptyInterpolation,
 }
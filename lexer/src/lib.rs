@@ -2,13 +2,14 @@ pub mod errors;
 pub mod tokens;
 pub mod utils;
 
-use std::{iter::Peekable, str::Chars};
+use rust_decimal::Decimal;
+use std::{iter::Peekable, ops::Range, str::Chars};
 
 use errors::LexicalError;
 
 use self::{
     errors::LexicalErrorKind,
-    tokens::{Token, TokenType},
+    tokens::{LosslessToken, StringPart, Token, TokenType, Trivia, TriviaKind},
     utils::*,
 };
 
@@ -17,6 +18,20 @@ pub struct Lexer<'a> {
     pub chars: Peekable<Chars<'a>>,
     pub current_pos: Position,
     pub current_lexeme: String,
+    /// Set after a line break consumed inside a token
+    line_break: bool,
+    source: &'a str,
+    /// Byte offset of the next character in the source
+    offset: usize,
+    /// Byte offset of the source in the enclosing one, used by the lexers of
+    /// interpolated expressions
+    base: usize,
+}
+
+/// Piece of source read by the lexer along with its byte range.
+enum Scanned {
+    Token(Token, Range<usize>),
+    Trivia(TriviaKind, Range<usize>),
 }
 
 impl<'a> Lexer<'a> {
@@ -25,23 +40,69 @@ impl<'a> Lexer<'a> {
             chars: source.chars().peekable(),
             current_pos: Position::new(0, 0, 0, 0),
             current_lexeme: String::new(),
+            line_break: false,
+            source,
+            offset: 0,
+            base: 0,
         }
     }
 
     pub fn tokenize(&mut self) -> Result<Vec<Token>, LexicalError> {
         let mut tokens: Vec<Token> = Vec::new();
 
-        while let Some(ch) = self.chars.next() {
+        self.scan(|scanned| {
+            if let Scanned::Token(token, _) = scanned {
+                tokens.push(token);
+            }
+        })?;
+
+        Ok(tokens)
+    }
+
+    /// Tokenizes the source without losing any of it, whitespaces and comments
+    /// are attached as trivia to the token following them so that the source
+    /// can be rebuilt from the tokens.
+    pub fn tokenize_lossless(&mut self) -> Result<Vec<LosslessToken>, LexicalError> {
+        let source = self.source;
+        let mut tokens: Vec<LosslessToken> = Vec::new();
+        let mut trivia: Vec<Trivia> = Vec::new();
+
+        self.scan(|scanned| match scanned {
+            Scanned::Token(token, range) => tokens.push(LosslessToken {
+                leading_trivia: std::mem::take(&mut trivia),
+                text: source[range].to_string(),
+                token,
+            }),
+            Scanned::Trivia(kind, range) => match trivia.last_mut() {
+                Some(last) if kind == TriviaKind::Whitespace && last.kind == kind => {
+                    last.text += &source[range];
+                }
+                _ => trivia.push(Trivia {
+                    kind,
+                    text: source[range].to_string(),
+                }),
+            },
+        })?;
+
+        Ok(tokens)
+    }
+
+    fn scan(&mut self, mut emit: impl FnMut(Scanned)) -> Result<(), LexicalError> {
+        while let Some(ch) = self.next_char() {
+            let start = self.offset - ch.len_utf8();
             self.current_pos.line_start = self.current_pos.line_end;
             self.current_pos.col_end = self.current_pos.col_start;
 
-            if self.is_skipable(ch) {
+            if let Some(kind) = self.skip_trivia(ch)? {
+                emit(Scanned::Trivia(kind, start..self.offset));
                 continue;
             }
 
             self.current_lexeme += &ch.to_string();
 
-            tokens.push(self.create_token(ch)?);
+            let mut token = self.create_token(ch)?;
+            token.span = Span::new(self.base + start, self.base + self.offset);
+            emit(Scanned::Token(token, start..self.offset));
 
             self.current_lexeme.clear();
 
@@ -56,38 +117,65 @@ impl<'a> Lexer<'a> {
 
         self.current_pos.col_end = self.current_pos.col_start;
 
-        let eof_token = Token::new(TokenType::Eof, String::new(), self.current_pos);
+        let mut eof_token = Token::new(TokenType::Eof, String::new(), self.current_pos);
+        eof_token.span = Span::new(self.base + self.offset, self.base + self.offset);
 
-        tokens.push(eof_token);
+        emit(Scanned::Token(eof_token, self.offset..self.offset));
 
-        Ok(tokens)
+        Ok(())
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        self.offset += ch.len_utf8();
+
+        Some(ch)
     }
 
     fn advance(&mut self) {
-        self.chars.next();
-        self.current_pos.col_end += 1;
+        self.next_char();
+
+        if self.line_break {
+            self.line_break = false;
+            self.current_pos.col_end = 0;
+        } else {
+            self.current_pos.col_end += 1;
+        }
+    }
+
+    /// Consumes a line break that is part of a token spanning multiple lines.
+    fn advance_line(&mut self) {
+        self.next_char();
+        self.current_pos.line_end += 1;
+        self.current_pos.col_end = 0;
+        self.line_break = true;
     }
 
-    fn is_skipable(&mut self, ch: char) -> bool {
+    fn skip_trivia(&mut self, ch: char) -> Result<Option<TriviaKind>, LexicalError> {
+        if ch == '<' && starts_with(&self.chars, "--") {
+            self.skip_block_comment()?;
+            return Ok(Some(TriviaKind::BlockComment));
+        }
+
         if ch == '-' {
             if let Some(next_char) = self.chars.peek() {
                 if *next_char == '-' {
                     self.skip_comment();
-                    return true;
+                    return Ok(Some(TriviaKind::Comment));
                 }
             }
         }
 
         if ch.is_whitespace() && !is_linebreak(ch) {
             self.current_pos.col_start = self.current_pos.col_end + 1;
-            return true;
+            return Ok(Some(TriviaKind::Whitespace));
         }
 
-        false
+        Ok(None)
     }
 
     fn skip_comment(&mut self) {
-        for ch in self.chars.by_ref() {
+        while let Some(ch) = self.next_char() {
             if ch == '\n' {
                 self.current_pos.line_end += 1;
                 self.current_pos.col_start = 0;
@@ -96,11 +184,65 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    fn skip_block_comment(&mut self) -> Result<(), LexicalError> {
+        let mut start = self.current_pos;
+        start.col_end = start.col_start + 2;
+
+        let mut depth = 1;
+        let mut col = self.current_pos.col_start + 3;
+        self.skip_chars(2);
+
+        while depth > 0 {
+            let ch = match self.next_char() {
+                Some(ch) => ch,
+                None => {
+                    return Err(LexicalError::new(
+                        LexicalErrorKind::UnterminatedComment,
+                        start,
+                    ))
+                }
+            };
+
+            if is_linebreak(ch) {
+                self.current_pos.line_end += 1;
+                col = 0;
+                continue;
+            }
+
+            col += 1;
+
+            if ch == '<' && starts_with(&self.chars, "--") {
+                self.skip_chars(2);
+                col += 2;
+                depth += 1;
+            } else if ch == '-' && starts_with(&self.chars, "->") {
+                self.skip_chars(2);
+                col += 2;
+                depth -= 1;
+            }
+        }
+
+        self.current_pos.col_start = col;
+
+        Ok(())
+    }
+
+    fn skip_chars(&mut self, count: usize) {
+        for _ in 0..count {
+            self.next_char();
+        }
+    }
+
     fn create_token(&mut self, ch: char) -> Result<Token, LexicalError> {
         match ch {
             ch if is_linebreak(ch) => self.create_linebreak(),
             ch if is_standard_symbol(ch) => self.create_symbol_token(),
-            ch if is_quote(ch) => self.create_string_token(ch),
+            ch if is_quote(ch) => self.create_string_token(ch, false),
+            'r' if matches!(self.chars.peek(), Some(ch) if is_quote(*ch)) => {
+                let quote_char = self.chars.peek().copied().unwrap();
+                self.push_next_char();
+                self.create_string_token(quote_char, true)
+            }
             ch if is_alphabetic(ch) => self.create_keyword_or_identifer_token(),
             ch if ch.is_ascii_digit() => self.create_number_token(),
             _ => Err(LexicalError::new(
@@ -169,90 +311,572 @@ impl<'a> Lexer<'a> {
         Ok(token)
     }
 
-    fn create_string_token(&mut self, quote_char: char) -> Result<Token, LexicalError> {
+    /// Creates a string token, triple quotes allow the string to span multiple
+    /// lines and raw strings keep backslashes and braces as they are.
+    fn create_string_token(&mut self, quote_char: char, raw: bool) -> Result<Token, LexicalError> {
+        let delimiter = quote_char.to_string().repeat(3);
+        let multiline = starts_with(&self.chars, &delimiter[1..]);
+        let mut indentation = 0;
         let mut closed = false;
+        let mut parts = Vec::new();
+        let mut value = String::new();
+
+        if multiline {
+            self.push_next_char();
+            self.push_next_char();
+            indentation = self.get_indentation(&delimiter, raw);
+
+            // the content starts on the next line when nothing follows the quotes
+            if self.is_blank_line() {
+                self.skip_indentation(usize::MAX);
 
-        while let Some(next_char) = self.chars.peek() {
-            if *next_char == '\\' {
+                if self.chars.peek().copied().is_some_and(is_linebreak) {
+                    self.current_lexeme.push('\n');
+                    self.advance_line();
+                    self.skip_indentation(indentation);
+                }
+            }
+        }
+
+        while let Some(next_char) = self.chars.peek().copied() {
+            if next_char == '\\' && !raw {
+                let start = self.next_char_position();
                 self.advance();
 
-                if let Some(next_next_char) = self.chars.peek() {
-                    let current_escape_char: String = format!("\\{}", next_next_char);
-                    let escape_char = match *next_next_char {
-                        'n' => "\n",
-                        't' => "\t",
-                        'r' => "\r",
-                        '\'' => "\'",
-                        '\"' => "\"",
-                        '\\' => "\\",
-                        _ => {
-                            return Err(LexicalError::new(
-                                LexicalErrorKind::InvalidEscapeChar(current_escape_char),
-                                self.current_pos,
-                            ))
-                        }
-                    };
-
-                    self.current_lexeme += escape_char;
-                    self.advance();
-                } else {
+                match self.read_escape_char(start)? {
+                    Some(escape_char) => {
+                        self.current_lexeme.push(escape_char);
+                        value.push(escape_char);
+                    }
+                    None => break,
+                }
+            } else if is_linebreak(next_char) {
+                if !multiline {
                     break;
-                };
-            } else if *next_char == '\n' {
+                }
+
+                self.current_lexeme.push(next_char);
+                self.advance_line();
+
+                // the line holding the closing quotes only sets the indentation
+                if self.is_closing_line(&delimiter) {
+                    self.skip_indentation(usize::MAX);
+                } else {
+                    value.push(next_char);
+                    self.skip_indentation(indentation);
+                }
+            } else if multiline && starts_with(&self.chars, &delimiter) {
+                closed = true;
+                for _ in 0..delimiter.len() {
+                    self.push_next_char();
+                }
                 break;
-            } else if *next_char == quote_char {
+            } else if !multiline && next_char == quote_char {
                 closed = true;
-                self.current_lexeme += &next_char.to_string();
+                self.current_lexeme.push(next_char);
                 self.advance();
                 break;
+            } else if next_char == '{' && !raw {
+                if !value.is_empty() {
+                    parts.push(StringPart::Literal(value.clone()));
+                    value.clear();
+                }
+
+                parts.push(StringPart::Expression(self.create_interpolation()?));
             } else {
-                self.current_lexeme += &next_char.to_string();
+                self.current_lexeme.push(next_char);
+                value.push(next_char);
                 self.advance();
             }
         }
 
         if !closed {
+            let kind = match multiline {
+                true => LexicalErrorKind::UnterminatedString,
+                false => LexicalErrorKind::TrailingQuote(quote_char),
+            };
+
+            return Err(LexicalError::new(kind, self.current_pos));
+        }
+
+        let token_type = if parts.is_empty() {
+            TokenType::String(value)
+        } else {
+            if !value.is_empty() {
+                parts.push(StringPart::Literal(value));
+            }
+
+            TokenType::InterpolatedString(parts)
+        };
+        let token = Token::new(token_type, self.current_lexeme.clone(), self.current_pos);
+
+        Ok(token)
+    }
+
+    /// Computes the indentation shared by the lines of a multi-line string,
+    /// blank lines are ignored but the line of the closing quotes is not.
+    fn get_indentation(&self, delimiter: &str, raw: bool) -> usize {
+        let mut chars = self.chars.clone();
+        let mut content = String::new();
+
+        while !starts_with(&chars, delimiter) {
+            match chars.next() {
+                Some('\\') if !raw => {
+                    content.push('\\');
+                    content.extend(chars.next());
+                }
+                Some(ch) => content.push(ch),
+                None => break,
+            }
+        }
+
+        let lines: Vec<&str> = content.split('\n').skip(1).collect();
+        let last = lines.len().saturating_sub(1);
+
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(index, line)| *index == last || !line.trim().is_empty())
+            .map(|(_, line)| line.chars().take_while(|ch| ch.is_whitespace()).count())
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn is_blank_line(&self) -> bool {
+        let mut chars = self.chars.clone();
+
+        while let Some(ch) = chars.next_if(|ch| !is_linebreak(*ch)) {
+            if !ch.is_whitespace() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn is_closing_line(&self, delimiter: &str) -> bool {
+        let mut chars = self.chars.clone();
+        while chars
+            .next_if(|ch| ch.is_whitespace() && !is_linebreak(*ch))
+            .is_some()
+        {}
+
+        starts_with(&chars, delimiter)
+    }
+
+    /// Skips at most `max` whitespaces at the start of a line.
+    fn skip_indentation(&mut self, max: usize) {
+        for _ in 0..max {
+            match self.chars.peek() {
+                Some(ch) if ch.is_whitespace() && !is_linebreak(*ch) => self.push_next_char(),
+                _ => break,
+            }
+        }
+    }
+
+    /// Reads the escape sequence following a backslash, `start` is the position
+    /// of the backslash.
+    fn read_escape_char(&mut self, start: Position) -> Result<Option<char>, LexicalError> {
+        let ch = match self.chars.peek() {
+            Some(ch) => *ch,
+            None => return Ok(None),
+        };
+        let mut sequence = format!("\\{ch}");
+        self.advance();
+
+        let escape_char = match ch {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\'' | '\"' | '\\' | '{' | '}' => Some(ch),
+            'x' => {
+                let digits = self.read_escape_digits(&mut sequence, 2);
+
+                match u32::from_str_radix(&digits, 16) {
+                    Ok(code) if digits.len() == 2 && code <= 0x7F => char::from_u32(code),
+                    _ => None,
+                }
+            }
+            'u' => return self.read_unicode_escape(sequence, start).map(Some),
+            _ => None,
+        };
+
+        match escape_char {
+            Some(escape_char) => Ok(Some(escape_char)),
+            None => Err(LexicalError::new(
+                LexicalErrorKind::InvalidEscapeChar(sequence),
+                self.escape_position(start),
+            )),
+        }
+    }
+
+    /// Reads the `{...}` part of a `\\u{...}` escape holding up to 6 hexadecimal
+    /// digits of a Unicode scalar value.
+    fn read_unicode_escape(
+        &mut self,
+        mut sequence: String,
+        start: Position,
+    ) -> Result<char, LexicalError> {
+        let mut escape_char = None;
+
+        if self.chars.peek() == Some(&'{') {
+            sequence.push('{');
+            self.advance();
+            let digits = self.read_escape_digits(&mut sequence, 6);
+
+            if self.chars.peek() == Some(&'}') {
+                sequence.push('}');
+                self.advance();
+                escape_char = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32);
+            }
+        }
+
+        escape_char.ok_or_else(|| {
+            LexicalError::new(
+                LexicalErrorKind::InvalidUnicodeEscape(sequence),
+                self.escape_position(start),
+            )
+        })
+    }
+
+    /// Reads up to `max` hexadecimal digits of an escape sequence.
+    fn read_escape_digits(&mut self, sequence: &mut String, max: usize) -> String {
+        let mut digits = String::new();
+
+        while let Some(ch) = self.chars.peek().copied() {
+            if digits.len() == max || !ch.is_ascii_hexdigit() {
+                break;
+            }
+
+            digits.push(ch);
+            sequence.push(ch);
+            self.advance();
+        }
+
+        digits
+    }
+
+    fn escape_position(&self, start: Position) -> Position {
+        let mut position = start;
+        position.col_end = self.current_pos.col_end;
+
+        position
+    }
+
+    /// Tokenizes an expression embedded in a string between braces, the
+    /// positions of its tokens point inside the string.
+    fn create_interpolation(&mut self) -> Result<Vec<Token>, LexicalError> {
+        self.advance();
+        let base = self.base + self.offset;
+        let (line, col) = (self.current_pos.line_end, self.current_pos.col_end);
+        let start = Position::new(line, col, line, col);
+
+        let mut source = String::new();
+        self.read_interpolation_source(&mut source, start)?;
+
+        if source.trim().is_empty() {
             return Err(LexicalError::new(
-                LexicalErrorKind::TrailingQuote(quote_char),
-                self.current_pos,
+                LexicalErrorKind::EmptyInterpolation,
+                start,
             ));
         }
 
-        let value = self.current_lexeme[1..self.current_lexeme.len() - 1].to_string();
-        let token = Token::new(
-            TokenType::String(value),
-            self.current_lexeme.clone(),
-            self.current_pos,
+        self.current_lexeme += &format!("{{{source}}}");
+
+        let mut lexer = Lexer::new(&source);
+        lexer.base = base;
+        lexer.current_pos = Position::new(
+            start.line_start,
+            start.col_start + 1,
+            start.line_end,
+            start.col_start + 1,
         );
 
-        Ok(token)
+        lexer.tokenize()
+    }
+
+    /// Reads the source up to the matching closing brace, skipping over nested
+    /// braces and strings.
+    fn read_interpolation_source(
+        &mut self,
+        source: &mut String,
+        start: Position,
+    ) -> Result<(), LexicalError> {
+        let mut depth = 1;
+
+        loop {
+            let ch = match self.chars.peek() {
+                Some(ch) if !is_linebreak(*ch) => *ch,
+                _ => {
+                    return Err(LexicalError::new(
+                        LexicalErrorKind::UnterminatedInterpolation,
+                        start,
+                    ))
+                }
+            };
+
+            self.advance();
+
+            match ch {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                ch if is_quote(ch) => {
+                    source.push(ch);
+                    self.read_nested_string_source(source, ch, start)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            source.push(ch);
+        }
+    }
+
+    fn read_nested_string_source(
+        &mut self,
+        source: &mut String,
+        quote_char: char,
+        start: Position,
+    ) -> Result<(), LexicalError> {
+        loop {
+            let ch = match self.chars.peek() {
+                Some(ch) if !is_linebreak(*ch) => *ch,
+                _ => {
+                    return Err(LexicalError::new(
+                        LexicalErrorKind::UnterminatedInterpolation,
+                        start,
+                    ))
+                }
+            };
+
+            self.advance();
+            source.push(ch);
+
+            if ch == quote_char {
+                return Ok(());
+            } else if ch == '\\' {
+                if let Some(next_char) = self.chars.peek() {
+                    source.push(*next_char);
+                    self.advance();
+                }
+            } else if ch == '{' {
+                self.read_interpolation_source(source, start)?;
+                source.push('}');
+            }
+        }
     }
 
     fn create_number_token(&mut self) -> Result<Token, LexicalError> {
-        while let Some(next_char) = self.chars.peek() {
-            if next_char.is_ascii_digit() || *next_char == '.' {
-                self.current_lexeme += &next_char.to_string();
-                self.advance();
-            } else {
-                break;
+        let radix = match (self.current_lexeme.as_str(), self.chars.peek()) {
+            ("0", Some('x' | 'X')) => 16,
+            ("0", Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+
+        let value = match radix {
+            10 => self.read_decimal_number()?,
+            _ => self.read_radix_number(radix)?,
+        };
+
+        if let Some(ch) = self.chars.peek() {
+            if is_alphanumeric(*ch) {
+                return Err(LexicalError::new(
+                    LexicalErrorKind::InvalidDigit(*ch, radix),
+                    self.next_char_position(),
+                ));
             }
         }
 
-        let token = match self.current_lexeme.parse::<f64>() {
-            Ok(value) => Token::new(
-                TokenType::Number(value),
-                self.current_lexeme.clone(),
-                self.current_pos,
-            ),
-            Err(_) => {
+        let token = Token::new(value, self.current_lexeme.clone(), self.current_pos);
+
+        Ok(token)
+    }
+
+    /// Reads a decimal literal, numbers without a fractional part or an
+    /// exponent are integers unless they don't fit in 64 bits.
+    fn read_decimal_number(&mut self) -> Result<TokenType, LexicalError> {
+        let mut number = self.read_digits(10, self.current_lexeme.clone())?;
+        let mut is_integer = true;
+
+        if self.is_decimal_point() {
+            is_integer = false;
+            self.push_next_char();
+            number.push('.');
+            number += &self.read_digits(10, String::new())?;
+        }
+
+        if let Some('e' | 'E') = self.chars.peek() {
+            is_integer = false;
+            self.push_next_char();
+            number.push('e');
+
+            if let Some(sign @ ('+' | '-')) = self.chars.peek().copied() {
+                self.push_next_char();
+                number.push(sign);
+            }
+
+            let exponent = self.read_digits(10, String::new())?;
+
+            if exponent.is_empty() {
                 return Err(LexicalError::new(
-                    LexicalErrorKind::InvalidFloat(self.current_lexeme.clone()),
+                    LexicalErrorKind::MissingDigits(self.current_lexeme.clone()),
                     self.current_pos,
+                ));
+            }
+
+            number += &exponent;
+        }
+
+        if self.is_decimal_point() {
+            return Err(LexicalError::new(
+                LexicalErrorKind::ExtraDecimalPoint,
+                self.next_char_position(),
+            ));
+        }
+
+        match self.chars.peek() {
+            Some('n') if is_integer => {
+                self.push_next_char();
+                return Ok(TokenType::BigInt(number.parse().unwrap()));
+            }
+            Some('n') => {
+                return Err(LexicalError::new(
+                    LexicalErrorKind::FractionalBigInt,
+                    self.next_char_position(),
                 ))
             }
+            Some('m') => {
+                self.push_next_char();
+                return self.parse_decimal(&number);
+            }
+            _ => {}
+        }
+
+        if is_integer {
+            if let Ok(value) = number.parse::<i64>() {
+                return Ok(TokenType::Integer(value));
+            }
+        }
+
+        number.parse::<f64>().map(TokenType::Number).map_err(|_| {
+            LexicalError::new(
+                LexicalErrorKind::InvalidFloat(self.current_lexeme.clone()),
+                self.current_pos,
+            )
+        })
+    }
+
+    /// Decimals are exact so literals with more digits than they can hold
+    /// are rejected instead of rounded.
+    fn parse_decimal(&self, number: &str) -> Result<TokenType, LexicalError> {
+        let value = match number.contains('e') {
+            true => Decimal::from_scientific(number),
+            false => Decimal::from_str_exact(number),
+        };
+
+        value.map(TokenType::Decimal).map_err(|_| {
+            LexicalError::new(
+                LexicalErrorKind::InvalidDecimal(self.current_lexeme.clone()),
+                self.current_pos,
+            )
+        })
+    }
+
+    fn read_radix_number(&mut self, radix: u32) -> Result<TokenType, LexicalError> {
+        self.push_next_char();
+        let digits = self.read_digits(radix, String::new())?;
+
+        if digits.is_empty() && !matches!(self.chars.peek(), Some(ch) if is_alphanumeric(*ch)) {
+            return Err(LexicalError::new(
+                LexicalErrorKind::MissingDigits(self.current_lexeme.clone()),
+                self.current_pos,
+            ));
+        }
+
+        if let Ok(value) = i64::from_str_radix(&digits, radix) {
+            return Ok(TokenType::Integer(value));
+        }
+
+        let value = digits.chars().fold(0.0, |value, digit| {
+            value * radix as f64 + digit.to_digit(radix).unwrap() as f64
+        });
+
+        Ok(TokenType::Number(value))
+    }
+
+    /// Reads the digits of a number, they can be separated by single
+    /// underscores which are left out of the returned digits.
+    fn read_digits(&mut self, radix: u32, mut digits: String) -> Result<String, LexicalError> {
+        let mut separator = false;
+
+        while let Some(ch) = self.chars.peek().copied() {
+            if ch == '_' {
+                if digits.is_empty() || separator {
+                    return Err(LexicalError::new(
+                        LexicalErrorKind::InvalidSeparator,
+                        self.next_char_position(),
+                    ));
+                }
+
+                separator = true;
+            } else if ch.is_digit(radix) {
+                digits.push(ch);
+                separator = false;
+            } else {
+                break;
+            }
+
+            self.push_next_char();
+        }
+
+        if separator {
+            let mut position = self.current_pos;
+            position.col_start = position.col_end;
+
+            return Err(LexicalError::new(
+                LexicalErrorKind::InvalidSeparator,
+                position,
+            ));
+        }
+
+        Ok(digits)
+    }
+
+    fn push_next_char(&mut self) {
+        if let Some(ch) = self.chars.peek() {
+            self.current_lexeme.push(*ch);
+            self.advance();
+        }
+    }
+
+    /// A dot followed by an identifier is left for a property access.
+    fn is_decimal_point(&self) -> bool {
+        let mut chars = self.chars.clone();
+
+        chars.next() == Some('.') && !matches!(chars.next(), Some(ch) if is_alphabetic(ch))
+    }
+
+    fn next_char_position(&self) -> Position {
+        let col = match self.line_break {
+            true => 0,
+            false => self.current_pos.col_end + 1,
         };
 
-        Ok(token)
+        Position::new(
+            self.current_pos.line_end,
+            col,
+            self.current_pos.line_end,
+            col,
+        )
     }
 
     fn create_keyword_or_identifer_token(&mut self) -> Result<Token, LexicalError> {
@@ -286,6 +910,9 @@ impl<'a> Lexer<'a> {
             "function" => TokenType::Function,
             "lambda" => TokenType::Lambda,
             "return" => TokenType::Return,
+            "try" => TokenType::Try,
+            "catch" => TokenType::Catch,
+            "export" => TokenType::Export,
             _ => TokenType::Identifier(self.current_lexeme.clone()),
         };
 
@@ -298,12 +925,36 @@ impl<'a> Lexer<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num_bigint::BigInt;
+
+    /// Tokenizes the source and clears the spans of the tokens so they can be
+    /// compared with the ones built by `Token::new`, spans are checked by
+    /// `test_spans`.
+    fn tokenize(source: &str) -> Vec<Token> {
+        let mut tokens = Lexer::new(source).tokenize().unwrap();
+        clear_spans(&mut tokens);
+
+        tokens
+    }
+
+    fn clear_spans(tokens: &mut [Token]) {
+        for token in tokens {
+            token.span = Span::default();
+
+            if let TokenType::InterpolatedString(parts) = &mut token.value {
+                for part in parts {
+                    if let StringPart::Expression(tokens) = part {
+                        clear_spans(tokens);
+                    }
+                }
+            }
+        }
+    }
 
     #[test]
     fn compare_single_line_tokens() {
         let s = "set s = 'Hello World';";
-        let mut lex = Lexer::new(s);
-        let tokens = lex.tokenize().unwrap();
+        let tokens = tokenize(s);
 
         assert_eq!(
             tokens,
@@ -343,8 +994,7 @@ mod tests {
         let s = r#"function hello() {
     return "Hello World";
 }"#;
-        let mut lex = Lexer::new(s);
-        let tokens = lex.tokenize().unwrap();
+        let tokens = tokenize(s);
 
         assert_eq!(
             tokens,
@@ -408,4 +1058,407 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_block_comments() {
+        let s = "set a <-- one <-- two -->
+still a comment --> = 1 <---->
+a";
+        let tokens = tokenize(s);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::new(
+                    TokenType::Set,
+                    String::from("set"),
+                    Position::new(0, 0, 0, 2)
+                ),
+                Token::new(
+                    TokenType::Identifier(String::from("a")),
+                    String::from("a"),
+                    Position::new(0, 4, 0, 4)
+                ),
+                Token::new(
+                    TokenType::Equal,
+                    String::from("="),
+                    Position::new(1, 20, 1, 20)
+                ),
+                Token::new(
+                    TokenType::Integer(1),
+                    String::from("1"),
+                    Position::new(1, 22, 1, 22)
+                ),
+                Token::new(
+                    TokenType::LineBreak,
+                    String::from("\n"),
+                    Position::new(1, 30, 1, 30)
+                ),
+                Token::new(
+                    TokenType::Identifier(String::from("a")),
+                    String::from("a"),
+                    Position::new(2, 0, 2, 0)
+                ),
+                Token::new(TokenType::Eof, String::new(), Position::new(2, 0, 2, 0)),
+            ]
+        );
+
+        let error = Lexer::new("a <-- <-- -->\n").tokenize().unwrap_err();
+
+        assert_eq!(error.kind, LexicalErrorKind::UnterminatedComment);
+        assert_eq!(error.position, Position::new(0, 2, 0, 4));
+        assert!(Lexer::new(include_str!("../../mock/syntax.ic"))
+            .tokenize()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let tokens = tokenize("'a {b} \\{c\\}'");
+        let parts = vec![
+            StringPart::Literal(String::from("a ")),
+            StringPart::Expression(vec![
+                Token::new(
+                    TokenType::Identifier(String::from("b")),
+                    String::from("b"),
+                    Position::new(0, 4, 0, 4),
+                ),
+                Token::new(TokenType::Eof, String::new(), Position::new(0, 4, 0, 4)),
+            ]),
+            StringPart::Literal(String::from(" {c}")),
+        ];
+
+        assert_eq!(
+            tokens[0],
+            Token::new(
+                TokenType::InterpolatedString(parts),
+                String::from("'a {b} {c}'"),
+                Position::new(0, 0, 0, 12)
+            )
+        );
+
+        let tokens = tokenize("\"{f('}')}\"");
+        let TokenType::InterpolatedString(parts) = &tokens[0].value else {
+            panic!("expected an interpolated string");
+        };
+
+        assert_eq!(parts.len(), 1);
+
+        let error = Lexer::new("set a = 'b {c'").tokenize().unwrap_err();
+
+        assert_eq!(error.kind, LexicalErrorKind::UnterminatedInterpolation);
+        assert_eq!(error.position, Position::new(0, 11, 0, 11));
+
+        let error = Lexer::new("'{ }'").tokenize().unwrap_err();
+
+        assert_eq!(error.kind, LexicalErrorKind::EmptyInterpolation);
+
+        let error = Lexer::new("'{a # b}'").tokenize().unwrap_err();
+
+        assert_eq!(
+            error.kind,
+            LexicalErrorKind::UnexpectedCharacter(String::from("#"))
+        );
+        assert_eq!(error.position, Position::new(0, 4, 0, 4));
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let literals = [
+            ("42", TokenType::Integer(42)),
+            ("2.75", TokenType::Number(2.75)),
+            ("1.", TokenType::Number(1.0)),
+            ("0xFF", TokenType::Integer(255)),
+            ("0Xff_ff", TokenType::Integer(65535)),
+            ("0b1010", TokenType::Integer(10)),
+            ("0B1111_0000", TokenType::Integer(240)),
+            ("1e-9", TokenType::Number(1e-9)),
+            ("2.5E+3", TokenType::Number(2500.0)),
+            ("6e2", TokenType::Number(600.0)),
+            ("1_000_000", TokenType::Integer(1_000_000)),
+            ("1_0.0_1e1_0", TokenType::Number(10.01e10)),
+            ("9223372036854775807", TokenType::Integer(i64::MAX)),
+            (
+                "9223372036854775808",
+                TokenType::Number(9223372036854775808.0),
+            ),
+            (
+                "0x1_0000_0000_0000_0000",
+                TokenType::Number(18446744073709551616.0),
+            ),
+            ("42n", TokenType::BigInt(42.into())),
+            (
+                "9_223_372_036_854_775_808n",
+                TokenType::BigInt(BigInt::from(i64::MAX) + 1),
+            ),
+            ("0.10m", TokenType::Decimal(Decimal::new(10, 2))),
+            ("1_000.50m", TokenType::Decimal(Decimal::new(100050, 2))),
+            ("7m", TokenType::Decimal(Decimal::new(7, 0))),
+            ("2.5e-3m", TokenType::Decimal(Decimal::new(25, 4))),
+        ];
+
+        for (source, value) in literals {
+            let tokens = tokenize(source);
+            let end = source.len() as u32 - 1;
+
+            assert_eq!(
+                tokens[0],
+                Token::new(value, String::from(source), Position::new(0, 0, 0, end)),
+                "round trip of '{source}'"
+            );
+        }
+
+        let errors = [
+            ("0b102", LexicalErrorKind::InvalidDigit('2', 2), (4, 4)),
+            ("0xFG", LexicalErrorKind::InvalidDigit('G', 16), (3, 3)),
+            ("12ab", LexicalErrorKind::InvalidDigit('a', 10), (2, 2)),
+            (
+                "0x",
+                LexicalErrorKind::MissingDigits(String::from("0x")),
+                (0, 1),
+            ),
+            (
+                "1e+",
+                LexicalErrorKind::MissingDigits(String::from("1e+")),
+                (0, 2),
+            ),
+            ("1.2.3", LexicalErrorKind::ExtraDecimalPoint, (3, 3)),
+            ("1__0", LexicalErrorKind::InvalidSeparator, (2, 2)),
+            ("10_", LexicalErrorKind::InvalidSeparator, (2, 2)),
+            ("0x_1", LexicalErrorKind::InvalidSeparator, (2, 2)),
+            ("1.5n", LexicalErrorKind::FractionalBigInt, (3, 3)),
+            ("1e3n", LexicalErrorKind::FractionalBigInt, (3, 3)),
+            ("0xffn", LexicalErrorKind::InvalidDigit('n', 16), (4, 4)),
+            ("2nd", LexicalErrorKind::InvalidDigit('d', 10), (2, 2)),
+            (
+                "0.12345678901234567890123456789m",
+                LexicalErrorKind::InvalidDecimal(String::from("0.12345678901234567890123456789m")),
+                (0, 31),
+            ),
+        ];
+
+        for (source, kind, (start, end)) in errors {
+            let source = format!("set a = {source}");
+            let error = Lexer::new(&source).tokenize().unwrap_err();
+
+            assert_eq!(error.kind, kind, "error of '{source}'");
+            assert_eq!(error.position, Position::new(0, start + 8, 0, end + 8));
+        }
+
+        let tokens = tokenize("1.max");
+
+        assert_eq!(tokens[0].value, TokenType::Integer(1));
+        assert_eq!(tokens[1].value, TokenType::Dot);
+    }
+
+    #[test]
+    fn test_unicode() {
+        let tokens = tokenize("set café = '\\u{1F976}\\x41\\0é'; été");
+
+        assert_eq!(
+            tokens[1],
+            Token::new(
+                TokenType::Identifier(String::from("café")),
+                String::from("café"),
+                Position::new(0, 4, 0, 7)
+            )
+        );
+        assert_eq!(
+            tokens[3].value,
+            TokenType::String(String::from("\u{1F976}A\0é"))
+        );
+        assert_eq!(tokens[3].pos, Position::new(0, 11, 0, 28));
+        assert_eq!(tokens[5].pos, Position::new(0, 31, 0, 33));
+
+        let errors = [
+            (
+                "'🥶\\u{110000}'",
+                LexicalErrorKind::InvalidUnicodeEscape(String::from("\\u{110000}")),
+            ),
+            (
+                "'🥶\\u{D800}'",
+                LexicalErrorKind::InvalidUnicodeEscape(String::from("\\u{D800}")),
+            ),
+            (
+                "'🥶\\u{}'",
+                LexicalErrorKind::InvalidUnicodeEscape(String::from("\\u{}")),
+            ),
+            (
+                "'🥶\\u41'",
+                LexicalErrorKind::InvalidUnicodeEscape(String::from("\\u")),
+            ),
+            (
+                "'🥶\\x8F'",
+                LexicalErrorKind::InvalidEscapeChar(String::from("\\x8F")),
+            ),
+            (
+                "'🥶\\x4'",
+                LexicalErrorKind::InvalidEscapeChar(String::from("\\x4")),
+            ),
+            (
+                "'🥶\\q'",
+                LexicalErrorKind::InvalidEscapeChar(String::from("\\q")),
+            ),
+        ];
+
+        for (source, kind) in errors {
+            let error = Lexer::new(source).tokenize().unwrap_err();
+            let (LexicalErrorKind::InvalidUnicodeEscape(sequence)
+            | LexicalErrorKind::InvalidEscapeChar(sequence)) = &kind
+            else {
+                unreachable!()
+            };
+            let end = sequence.chars().count() as u32 + 1;
+
+            assert_eq!(error.kind, kind);
+            assert_eq!(error.position, Position::new(0, 2, 0, end));
+        }
+
+        let error = Lexer::new("'日本語' @").tokenize().unwrap_err();
+
+        assert_eq!(error.position, Position::new(0, 6, 0, 6));
+    }
+
+    #[test]
+    fn test_multiline_strings() {
+        let source = "set a = \"\"\"
+    Hello {name},
+      indented\\t
+
+    end
+    \"\"\";
+a";
+        let tokens = tokenize(source);
+        let TokenType::InterpolatedString(parts) = &tokens[3].value else {
+            panic!("expected an interpolated string");
+        };
+
+        assert_eq!(parts[0], StringPart::Literal(String::from("Hello ")));
+        assert_eq!(
+            parts[1],
+            StringPart::Expression(vec![
+                Token::new(
+                    TokenType::Identifier(String::from("name")),
+                    String::from("name"),
+                    Position::new(1, 11, 1, 14)
+                ),
+                Token::new(TokenType::Eof, String::new(), Position::new(1, 14, 1, 14)),
+            ])
+        );
+        assert_eq!(
+            parts[2],
+            StringPart::Literal(String::from(",\n  indented\t\n\nend"))
+        );
+        assert_eq!(tokens[3].pos, Position::new(0, 8, 5, 6));
+        assert_eq!(tokens[4].pos, Position::new(5, 7, 5, 7));
+        assert_eq!(tokens[6].pos, Position::new(6, 0, 6, 0));
+
+        let tokens = tokenize("'''a\n   b'''");
+
+        assert_eq!(tokens[0].value, TokenType::String(String::from("a\nb")));
+        assert_eq!(tokens[0].pos, Position::new(0, 0, 1, 6));
+
+        let tokens = tokenize("\"\"\"\"\"\" + ''");
+
+        assert_eq!(tokens[0].value, TokenType::String(String::new()));
+        assert_eq!(tokens[2].value, TokenType::String(String::new()));
+
+        let error = Lexer::new("\"\"\"\n  a\n").tokenize().unwrap_err();
+
+        assert_eq!(error.kind, LexicalErrorKind::UnterminatedString);
+        assert_eq!(error.position, Position::new(0, 0, 2, 0));
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let tokens = tokenize("r'C:\\new\\{dir}' r\"\"\"\n  \\d+\n  \"\"\" r");
+
+        assert_eq!(
+            tokens[0],
+            Token::new(
+                TokenType::String(String::from("C:\\new\\{dir}")),
+                String::from("r'C:\\new\\{dir}'"),
+                Position::new(0, 0, 0, 14)
+            )
+        );
+        assert_eq!(tokens[1].value, TokenType::String(String::from("\\d+")));
+        assert_eq!(tokens[1].pos, Position::new(0, 16, 2, 4));
+        assert_eq!(tokens[2].value, TokenType::Identifier(String::from("r")));
+        assert_eq!(tokens[2].pos, Position::new(2, 6, 2, 6));
+    }
+
+    #[test]
+    fn test_lossless() {
+        let source = "set a = 1 -- one\n<-- block\n  comment -->  print(\"{a}\\t\" + 'é')\n\n  ";
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+        let text: String = tokens.iter().map(LosslessToken::full_text).collect();
+
+        assert_eq!(text, source);
+        assert_eq!(
+            tokens.iter().map(|t| t.token.clone()).collect::<Vec<_>>(),
+            Lexer::new(source).tokenize().unwrap()
+        );
+
+        assert_eq!(tokens[3].text, "1");
+        assert_eq!(
+            tokens[4].leading_trivia,
+            vec![
+                Trivia {
+                    kind: TriviaKind::Whitespace,
+                    text: String::from(" "),
+                },
+                Trivia {
+                    kind: TriviaKind::Comment,
+                    text: String::from("-- one\n"),
+                },
+                Trivia {
+                    kind: TriviaKind::BlockComment,
+                    text: String::from("<-- block\n  comment -->"),
+                },
+                Trivia {
+                    kind: TriviaKind::Whitespace,
+                    text: String::from("  "),
+                },
+            ]
+        );
+        assert_eq!(tokens[6].text, "\"{a}\\t\"");
+
+        let eof = tokens.last().unwrap();
+        assert_eq!(eof.token.value, TokenType::Eof);
+        assert_eq!(eof.leading_trivia[0].text, "  ");
+    }
+
+    #[test]
+    fn test_spans() {
+        let source = "set é = \"a{b + 1}\"\n-- c\n0x1F";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let spans: Vec<_> = tokens.iter().map(|t| (t.span.start, t.span.end)).collect();
+
+        assert_eq!(
+            spans,
+            [
+                (0, 3),
+                (4, 6),
+                (7, 8),
+                (9, 19),
+                (19, 20),
+                (25, 29),
+                (29, 29)
+            ]
+        );
+        assert_eq!(&source[tokens[1].span.start..tokens[1].span.end], "é");
+
+        let TokenType::InterpolatedString(parts) = &tokens[3].value else {
+            panic!("expected an interpolated string");
+        };
+        let StringPart::Expression(expression) = &parts[1] else {
+            panic!("expected an expression");
+        };
+        let spans: Vec<_> = expression
+            .iter()
+            .map(|t| (t.span.start, t.span.end))
+            .collect();
+
+        assert_eq!(spans, [(12, 13), (14, 15), (16, 17), (17, 17)]);
+    }
 }
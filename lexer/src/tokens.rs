@@ -1,9 +1,18 @@
-use super::utils::Position;
+use super::utils::{Position, Span};
+
+use num_bigint::BigInt;
+use rust_decimal::Decimal;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     Number(f64),
+    Integer(i64),
+    /// Integer literal with the `n` suffix
+    BigInt(BigInt),
+    /// Decimal literal with the `m` suffix
+    Decimal(Decimal),
     String(String),
+    InterpolatedString(Vec<StringPart>),
 
     Identifier(String),
 
@@ -27,6 +36,9 @@ pub enum TokenType {
     Function,
     Lambda,
     Return,
+    Try,
+    Catch,
+    Export,
 
     Plus,
     Minus,
@@ -83,26 +95,10 @@ impl TokenType {
         matches!(self, TokenType::Eof)
     }
 
-    pub fn is_equality(&self) -> bool {
-        matches!(self, TokenType::EqualEqual | TokenType::BangEqual)
-    }
-
     pub fn is_range(&self) -> bool {
         matches!(self, TokenType::To)
     }
 
-    pub fn is_term_op(&self) -> bool {
-        matches!(self, TokenType::Plus | TokenType::Minus | TokenType::Modulo)
-    }
-
-    pub fn is_factor_op(&self) -> bool {
-        matches!(self, TokenType::Asterix | TokenType::Slash)
-    }
-
-    pub fn is_binary_operator(&self) -> bool {
-        self.is_comparaison() || self.is_factor_op() || self.is_term_op()
-    }
-
     pub fn is_unary(&self) -> bool {
         matches!(self, TokenType::Bang | TokenType::Minus)
     }
@@ -123,18 +119,15 @@ impl TokenType {
         )
     }
 
-    pub fn is_comparaison(&self) -> bool {
-        matches!(
-            self,
-            TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual,
-        )
-    }
-
     pub fn is_literal(&self) -> bool {
         matches!(
             self,
             TokenType::String(_)
+                | TokenType::InterpolatedString(_)
                 | TokenType::Number(_)
+                | TokenType::Integer(_)
+                | TokenType::BigInt(_)
+                | TokenType::Decimal(_)
                 | TokenType::True
                 | TokenType::False
                 | TokenType::Null
@@ -162,6 +155,9 @@ impl TokenType {
                 | TokenType::Continue
                 | TokenType::Function
                 | TokenType::Return
+                | TokenType::Try
+                | TokenType::Catch
+                | TokenType::Export
         )
     }
 
@@ -170,19 +166,80 @@ impl TokenType {
             && !self.is_keyword()
             && !self.is_line_break()
             && !self.is_eof()
-            && !matches!(self, TokenType::String(_) | TokenType::Number(_))
+            && !matches!(
+                self,
+                TokenType::String(_)
+                    | TokenType::InterpolatedString(_)
+                    | TokenType::Number(_)
+                    | TokenType::Integer(_)
+                    | TokenType::BigInt(_)
+                    | TokenType::Decimal(_)
+            )
     }
 }
 
+/// Piece of an interpolated string, embedded expressions are kept as the
+/// tokens they are made of.
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringPart {
+    Literal(String),
+    Expression(Vec<Token>),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Token {
     pub value: TokenType,
     pub lexeme: String,
     pub pos: Position,
+    /// Byte range of the token in the source, set by the lexer
+    pub span: Span,
 }
 
 impl Token {
     pub fn new(value: TokenType, lexeme: String, pos: Position) -> Self {
-        Self { value, lexeme, pos }
+        Self {
+            value,
+            lexeme,
+            pos,
+            span: Span::default(),
+        }
+    }
+}
+
+/// Source text ignored by the parser, only kept by the lossless mode of the
+/// lexer.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TriviaKind {
+    Whitespace,
+    Comment,
+    BlockComment,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+/// Token produced by the lossless mode of the lexer, it holds the exact source
+/// text of the token and the trivia preceding it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LosslessToken {
+    pub leading_trivia: Vec<Trivia>,
+    pub text: String,
+    pub token: Token,
+}
+
+impl LosslessToken {
+    /// The source text of the token including its leading trivia.
+    pub fn full_text(&self) -> String {
+        let mut text: String = self
+            .leading_trivia
+            .iter()
+            .map(|t| t.text.as_str())
+            .collect();
+        text += &self.text;
+
+        text
     }
 }
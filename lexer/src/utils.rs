@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, iter::Peekable, str::Chars};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Position {
@@ -23,18 +23,36 @@ impl fmt::Display for Position {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let col = match self.col_start == self.col_end {
             true => (self.col_start + 1).to_string(),
-            false => format!("{}-{}", self.col_start, self.col_end),
+            false => format!("{}-{}", self.col_start + 1, self.col_end + 1),
         };
 
         let line = match self.line_start == self.line_end {
             true => (self.line_start + 1).to_string(),
-            false => format!("{}-{}", self.line_start, self.line_end),
+            false => format!("{}-{}", self.line_start + 1, self.line_end + 1),
         };
 
         write!(f, "line {}, col {}", line, col)
     }
 }
 
+/// Byte range of a piece of source, the end is exclusive.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The span going from the start of this span to the end of `other`.
+    pub fn to(self, other: Span) -> Self {
+        Self::new(self.start, other.end.max(self.start))
+    }
+}
+
 pub fn is_standard_symbol(ch: char) -> bool {
     let symbols = "+-%*/(){}[].,;:!<>=";
     symbols.contains(ch)
@@ -44,14 +62,35 @@ pub fn is_quote(ch: char) -> bool {
     ch == '\"' || ch == '\''
 }
 
+/// Checks if the character can start an identifier, any Unicode letter is
+/// accepted unless the `xid` feature restricts it to `XID_Start`.
+#[cfg(not(feature = "xid"))]
 pub fn is_alphabetic(ch: char) -> bool {
-    ch.is_ascii_alphabetic() || ch == '_'
+    ch.is_alphabetic() || ch == '_'
 }
 
+#[cfg(feature = "xid")]
+pub fn is_alphabetic(ch: char) -> bool {
+    unicode_ident::is_xid_start(ch) || ch == '_'
+}
+
+/// Checks if the character can continue an identifier, see `is_alphabetic`.
+#[cfg(not(feature = "xid"))]
 pub fn is_alphanumeric(ch: char) -> bool {
-    ch.is_ascii_alphanumeric() || ch == '_'
+    ch.is_alphanumeric() || ch == '_'
+}
+
+#[cfg(feature = "xid")]
+pub fn is_alphanumeric(ch: char) -> bool {
+    unicode_ident::is_xid_continue(ch)
 }
 
 pub fn is_linebreak(ch: char) -> bool {
     ch == '\n'
 }
+
+/// Checks if the next characters match the pattern without consuming them.
+pub fn starts_with(chars: &Peekable<Chars>, pattern: &str) -> bool {
+    let mut chars = chars.clone();
+    pattern.chars().all(|ch| chars.next() == Some(ch))
+}
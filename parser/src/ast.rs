@@ -1,10 +1,11 @@
 use std::fmt;
 
-use lexer::tokens::Token;
+use lexer::{tokens::Token, utils::Span};
 
 #[derive(Debug, Clone)]
 pub enum Expression {
     LiteralExpression(Literal),
+    InterpolatedString(Interpolation),
     VariableExpression(Variable),
     AssignementExpression(Assign),
     ArrayExpression(Array),
@@ -19,10 +20,32 @@ pub enum Expression {
     LambdaFunction(Lambda),
 }
 
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::LiteralExpression(e) => e.token.span,
+            Expression::InterpolatedString(e) => e.token.span,
+            Expression::VariableExpression(e) => e.token.span,
+            Expression::AssignementExpression(e) => e.span,
+            Expression::ArrayExpression(e) => e.span,
+            Expression::ObjectExpression(e) => e.span,
+            Expression::IndexExpression(e) => e.span,
+            Expression::PropAccess(e) => e.span,
+            Expression::UnaryExpression(e) => e.span,
+            Expression::BinaryExpression(e) => e.span,
+            Expression::IfExpression(e) => e.span,
+            Expression::MatchExpression(e) => e.span,
+            Expression::FunctionCall(e) => e.span,
+            Expression::LambdaFunction(e) => e.span,
+        }
+    }
+}
+
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Expression::LiteralExpression(e) => write!(f, "{e}"),
+            Expression::InterpolatedString(e) => write!(f, "{e}"),
             Expression::VariableExpression(e) => write!(f, "{e}"),
             Expression::UnaryExpression(e) => write!(f, "{e}"),
             Expression::BinaryExpression(e) => write!(f, "{e}"),
@@ -50,11 +73,30 @@ impl fmt::Display for Literal {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Interpolation {
+    pub token: Token,
+    pub parts: Vec<InterpolationPart>,
+}
+
+#[derive(Debug, Clone)]
+pub enum InterpolationPart {
+    Literal(String),
+    Expression(Expression),
+}
+
+impl fmt::Display for Interpolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.token.lexeme)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Assign {
     pub left: Box<Expression>,
     pub token: Token,
     pub value: Box<Expression>,
+    pub span: Span,
 }
 
 impl fmt::Display for Assign {
@@ -66,6 +108,7 @@ impl fmt::Display for Assign {
 #[derive(Debug, Clone)]
 pub struct Array {
     pub items: Vec<Expression>,
+    pub span: Span,
 }
 
 impl fmt::Display for Array {
@@ -85,6 +128,7 @@ impl fmt::Display for Array {
 #[derive(Debug, Clone)]
 pub struct Object {
     pub props: Vec<(Token, Expression)>,
+    pub span: Span,
 }
 
 impl fmt::Display for Object {
@@ -106,6 +150,7 @@ pub struct Index {
     pub token: Token,
     pub expression: Box<Expression>,
     pub index: Box<Expression>,
+    pub span: Span,
 }
 
 impl fmt::Display for Index {
@@ -119,6 +164,7 @@ pub struct Access {
     pub token: Token,
     pub expression: Box<Expression>,
     pub prop: Token,
+    pub span: Span,
 }
 
 impl fmt::Display for Access {
@@ -142,6 +188,7 @@ impl fmt::Display for Variable {
 pub struct Unary {
     pub operator: Token,
     pub operand: Box<Expression>,
+    pub span: Span,
 }
 
 impl fmt::Display for Unary {
@@ -155,6 +202,7 @@ pub struct Binary {
     pub left: Box<Expression>,
     pub operator: Token,
     pub right: Box<Expression>,
+    pub span: Span,
 }
 
 impl fmt::Display for Binary {
@@ -168,6 +216,7 @@ pub struct If {
     pub condition: Box<Expression>,
     pub true_branch: Box<Statement>,
     pub else_branch: Option<Box<Statement>>,
+    pub span: Span,
 }
 
 impl fmt::Display for If {
@@ -188,6 +237,7 @@ impl fmt::Display for If {
 pub struct MatchArm {
     pub pattern: Vec<Box<Expression>>,
     pub block: Box<Statement>,
+    pub span: Span,
 }
 
 impl fmt::Display for MatchArm {
@@ -209,6 +259,7 @@ pub struct Match {
     pub pattern: Box<Expression>,
     pub arms: Vec<MatchArm>,
     pub default: Option<MatchArm>,
+    pub span: Span,
 }
 
 impl fmt::Display for Match {
@@ -230,6 +281,7 @@ pub struct Call {
     pub object: Option<Box<Expression>>,
     pub caller: Box<Expression>,
     pub arguments: Vec<Expression>,
+    pub span: Span,
 }
 
 impl fmt::Display for Call {
@@ -250,6 +302,7 @@ impl fmt::Display for Call {
 pub struct Lambda {
     pub parameter: Vec<Token>,
     pub body: Box<Statement>,
+    pub span: Span,
 }
 
 impl fmt::Display for Lambda {
@@ -269,21 +322,45 @@ impl fmt::Display for Lambda {
 #[derive(Debug, Clone)]
 pub enum Statement {
     VariableDeclaration(Declaration),
+    DestructuringDeclaration(Destructuring),
     ExpressionStatement(Expression),
     BlockStatement(Block),
-    ForStatement(For),
+    ForStatement(Box<For>),
     WhileStatement(While),
     LoopStatement(Loop),
     BreakStatement(Break),
     ContinueStatement(Continue),
     FunctionDeclaration(FunctionDeclaration),
     ReturnStatement(Return),
+    TryStatement(Try),
+    ExportStatement(Export),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::VariableDeclaration(s) => s.span,
+            Statement::DestructuringDeclaration(s) => s.span,
+            Statement::ExpressionStatement(s) => s.span(),
+            Statement::BlockStatement(s) => s.span,
+            Statement::ForStatement(s) => s.span,
+            Statement::WhileStatement(s) => s.span,
+            Statement::LoopStatement(s) => s.span,
+            Statement::BreakStatement(s) => s.span,
+            Statement::ContinueStatement(s) => s.span,
+            Statement::FunctionDeclaration(s) => s.span,
+            Statement::ReturnStatement(s) => s.span,
+            Statement::TryStatement(s) => s.span,
+            Statement::ExportStatement(s) => s.span,
+        }
+    }
 }
 
 impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Statement::VariableDeclaration(s) => write!(f, "{s}"),
+            Statement::DestructuringDeclaration(s) => write!(f, "{s}"),
             Statement::ExpressionStatement(s) => write!(f, "{s}"),
             Statement::BlockStatement(s) => write!(f, "{s}"),
             Statement::WhileStatement(s) => write!(f, "{s}"),
@@ -293,6 +370,8 @@ impl fmt::Display for Statement {
             Statement::FunctionDeclaration(s) => write!(f, "{s}"),
             Statement::ReturnStatement(s) => write!(f, "{s}"),
             Statement::ForStatement(s) => write!(f, "{s}"),
+            Statement::TryStatement(s) => write!(f, "{s}"),
+            Statement::ExportStatement(s) => write!(f, "{s}"),
         }
     }
 }
@@ -300,6 +379,7 @@ impl fmt::Display for Statement {
 #[derive(Debug, Clone)]
 pub struct Block {
     pub statements: Vec<Statement>,
+    pub span: Span,
 }
 
 impl fmt::Display for Block {
@@ -316,6 +396,7 @@ impl fmt::Display for Block {
 pub struct Declaration {
     pub name: Token,
     pub value: Expression,
+    pub span: Span,
 }
 
 impl fmt::Display for Declaration {
@@ -324,12 +405,28 @@ impl fmt::Display for Declaration {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Destructuring {
+    pub names: Vec<Token>,
+    pub value: Expression,
+    pub span: Span,
+}
+
+impl fmt::Display for Destructuring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self.names.iter().map(|name| name.lexeme.as_str()).collect();
+        write!(f, "set {{ {} }} = {}", names.join(", "), self.value)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct For {
+    pub label: Option<Token>,
     pub variable: (Token, Option<Token>),
     pub iterable: Expression,
     pub iterable_token: Token,
     pub block: Box<Statement>,
+    pub span: Span,
 }
 
 impl fmt::Display for For {
@@ -339,52 +436,93 @@ impl fmt::Display for For {
         } else {
             String::from(&self.variable.0.lexeme)
         };
-        write!(f, "for {} in {} {}", variables, self.iterable, self.block)
+        write!(
+            f,
+            "{}for {} in {} {}",
+            Label(&self.label),
+            variables,
+            self.iterable,
+            self.block
+        )
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct While {
+    pub label: Option<Token>,
     pub condition: Expression,
     pub block: Box<Statement>,
+    pub span: Span,
 }
 
 impl fmt::Display for While {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "while ({}) {}", self.condition, self.block)
+        write!(
+            f,
+            "{}while ({}) {}",
+            Label(&self.label),
+            self.condition,
+            self.block
+        )
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Loop {
+    pub label: Option<Token>,
     pub block: Box<Statement>,
+    pub span: Span,
 }
 
 impl fmt::Display for Loop {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "loop {}", self.block)
+        write!(f, "{}loop {}", Label(&self.label), self.block)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Break {
     pub token: Token,
+    /// Label of the loop to exit, the innermost one when missing
+    pub label: Option<Token>,
+    pub span: Span,
 }
 
 impl fmt::Display for Break {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "break")
+        match &self.label {
+            Some(label) => write!(f, "break {}", label.lexeme),
+            None => write!(f, "break"),
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Continue {
     pub token: Token,
+    /// Label of the loop to continue, the innermost one when missing
+    pub label: Option<Token>,
+    pub span: Span,
 }
 
 impl fmt::Display for Continue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "continue")
+        match &self.label {
+            Some(label) => write!(f, "continue {}", label.lexeme),
+            None => write!(f, "continue"),
+        }
+    }
+}
+
+/// Displays the label of a loop followed by its colon.
+struct Label<'a>(&'a Option<Token>);
+
+impl fmt::Display for Label<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(label) => write!(f, "{}: ", label.lexeme),
+            None => Ok(()),
+        }
     }
 }
 
@@ -393,6 +531,7 @@ pub struct FunctionDeclaration {
     pub token: Option<Token>,
     pub parameter: Vec<Token>,
     pub body: Box<Statement>,
+    pub span: Span,
 }
 
 impl fmt::Display for FunctionDeclaration {
@@ -417,6 +556,7 @@ impl fmt::Display for FunctionDeclaration {
 pub struct Return {
     pub token: Token,
     pub expression: Expression,
+    pub span: Span,
 }
 
 impl fmt::Display for Return {
@@ -424,3 +564,46 @@ impl fmt::Display for Return {
         write!(f, "return {}", self.expression)
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub token: Token,
+    pub block: Box<Statement>,
+    pub error: Token,
+    pub handler: Box<Statement>,
+    pub span: Span,
+}
+
+impl fmt::Display for Try {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "try {} catch ({}) {}",
+            self.block, self.error.lexeme, self.handler
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub token: Token,
+    pub declaration: Box<Statement>,
+    pub span: Span,
+}
+
+impl Export {
+    pub fn get_names(&self) -> Vec<&Token> {
+        match self.declaration.as_ref() {
+            Statement::VariableDeclaration(declaration) => vec![&declaration.name],
+            Statement::DestructuringDeclaration(declaration) => declaration.names.iter().collect(),
+            Statement::FunctionDeclaration(declaration) => declaration.token.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Export {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "export {}", self.declaration)
+    }
+}
@@ -0,0 +1,163 @@
+use std::ops::Range;
+
+use lexer::{
+    tokens::{LosslessToken, Token, TokenType},
+    utils::Position,
+};
+
+use crate::{ast::Statement, error::ParsingError, Parser};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SyntaxKind {
+    Program,
+    Statement,
+    Block,
+    /// Statement that failed to parse
+    Error,
+    Literal,
+    Interpolation,
+    Identifier,
+    Group,
+    Array,
+    Object,
+    Unary,
+    Binary,
+    Assignment,
+    Index,
+    Property,
+    Call,
+    /// Parenthesized arguments of a call
+    ArgumentList,
+    /// Parenthesized parameters of a function or a lambda
+    ParameterList,
+    Lambda,
+    If,
+    Match,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(LosslessToken),
+}
+
+/// Node of the concrete syntax tree, unlike the AST it keeps every token of
+/// the source along with the whitespaces and comments around them.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    fn new(kind: SyntaxKind) -> Self {
+        Self {
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    /// The source text covered by the node, the text of the program node is
+    /// the exact source it was parsed from.
+    pub fn text(&self) -> String {
+        self.tokens()
+            .iter()
+            .map(|token| token.full_text())
+            .collect()
+    }
+
+    /// Every token of the node in source order, including the ones of the
+    /// nested nodes.
+    pub fn tokens(&self) -> Vec<&LosslessToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a LosslessToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// The direct child nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Derives the AST of the node. The tree only records where the nodes
+    /// start and end, so the tokens of the node are parsed again without the
+    /// trivia.
+    pub fn to_ast(&self) -> Result<Vec<Statement>, ParsingError> {
+        let mut tokens: Vec<Token> = self
+            .tokens()
+            .into_iter()
+            .map(|token| token.token.clone())
+            .collect();
+
+        if !tokens.last().is_some_and(|token| token.value.is_eof()) {
+            let pos = match tokens.last() {
+                Some(token) => token.pos,
+                None => Position::new(0, 0, 0, 0),
+            };
+            tokens.push(Token::new(TokenType::Eof, String::new(), pos));
+        }
+
+        Parser::new(&tokens).parse()
+    }
+}
+
+/// Builds the concrete syntax tree of the tokens produced by the lossless mode
+/// of the lexer. The tree is built even when the source has syntax errors, the
+/// statements that failed to parse end up in `Error` nodes.
+pub fn parse(tokens: Vec<LosslessToken>) -> (SyntaxNode, Vec<ParsingError>) {
+    let significant: Vec<Token> = tokens.iter().map(|token| token.token.clone()).collect();
+    let mut parser = Parser::new(&significant);
+    parser.record_syntax = true;
+    let (_, errors) = parser.parse_with_recovery();
+
+    let mut ranges = std::mem::take(&mut parser.syntax_ranges);
+    // nodes are recorded after the nodes they contain but parents must come
+    // first, even when they cover the same tokens
+    ranges.reverse();
+    ranges.sort_by_key(|(_, range)| (range.start, usize::MAX - range.end));
+
+    (build_tree(tokens, ranges), errors)
+}
+
+fn build_tree(tokens: Vec<LosslessToken>, ranges: Vec<(SyntaxKind, Range<usize>)>) -> SyntaxNode {
+    let mut stack = vec![(SyntaxNode::new(SyntaxKind::Program), usize::MAX)];
+    let mut ranges = ranges.into_iter().peekable();
+
+    for (index, token) in tokens.into_iter().enumerate() {
+        while stack.last().is_some_and(|(_, end)| *end <= index) {
+            close_node(&mut stack);
+        }
+
+        while let Some((kind, range)) = ranges.next_if(|(_, range)| range.start == index) {
+            stack.push((SyntaxNode::new(kind), range.end));
+        }
+
+        let (node, _) = stack.last_mut().unwrap();
+        node.children.push(SyntaxElement::Token(token));
+    }
+
+    while stack.len() > 1 {
+        close_node(&mut stack);
+    }
+
+    stack.pop().unwrap().0
+}
+
+fn close_node(stack: &mut Vec<(SyntaxNode, usize)>) {
+    let (node, _) = stack.pop().unwrap();
+    let (parent, _) = stack.last_mut().unwrap();
+    parent.children.push(SyntaxElement::Node(node));
+}
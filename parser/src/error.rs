@@ -53,12 +53,22 @@ pub enum ParsingErrorKind {
     ExpectedIdentifier(String),
     #[error("expected 'in' but got '{0}'")]
     ExpectedIn(String),
+    #[error("expected 'catch' but got '{0}'")]
+    ExpectedCatch(String),
+    #[error("expected a declaration after 'export' but got '{0}'")]
+    ExpectedDeclaration(String),
     #[error("missing match arm expression")]
     MissingArmExpression,
     #[error("invalid assignment target")]
     InvalidAssignment,
     #[error("invalid property name '{0}'")]
     InvalidProp(String),
+    #[error("expected a loop after the label but got '{0}'")]
+    ExpectedLoop(String),
+    #[error("'{0}' outside of a loop")]
+    OutsideLoop(String),
+    #[error("undeclared label '{0}'")]
+    UndeclaredLabel(String),
     #[error("{0}")]
     LexicalError(LexicalErrorKind),
 }
@@ -1,15 +1,62 @@
 pub mod ast;
+pub mod cst;
 pub mod error;
 
-use self::{ast::*, error::ParsingErrorKind};
+use self::{ast::*, cst::SyntaxKind, error::ParsingErrorKind};
 
 use error::ParsingError;
-use lexer::tokens::{Token, TokenType};
-use std::{iter::Peekable, slice::Iter, vec};
+use lexer::{
+    tokens::{StringPart, Token, TokenType},
+    utils::Span,
+};
+use std::{iter::Peekable, ops::Range, slice::Iter, vec};
+
+#[derive(Debug, PartialEq)]
+enum Associativity {
+    Left,
+    None,
+}
+
+/// Precedence and associativity of the binary operators, higher binds tighter.
+/// Non-associative operators can't be chained without parentheses.
+fn get_binary_precedence(token: &TokenType) -> Option<(u8, Associativity)> {
+    let precedence = match token {
+        TokenType::Or => (1, Associativity::Left),
+        TokenType::And => (2, Associativity::Left),
+        TokenType::EqualEqual | TokenType::BangEqual => (3, Associativity::Left),
+        TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
+            (4, Associativity::Left)
+        }
+        TokenType::To => (5, Associativity::None),
+        TokenType::Plus | TokenType::Minus => (6, Associativity::Left),
+        TokenType::Asterix | TokenType::Slash | TokenType::Modulo => (7, Associativity::Left),
+        _ => return None,
+    };
+
+    Some(precedence)
+}
 
 pub struct Parser<'a> {
     pub tokens: Peekable<Iter<'a, Token>>,
     pub current_token: &'a Token,
+    errors: Vec<ParsingError>,
+    /// Whether a line break or a semicolon precedes the current token
+    after_separator: bool,
+    /// Number of tokens consumed so far
+    index: usize,
+    /// Index following the last token consumed before the current one
+    previous_end: usize,
+    /// Span of the last token consumed before the current one
+    previous_span: Span,
+    /// Whether the ranges of the syntax nodes are recorded, they are only
+    /// needed to build a concrete syntax tree
+    record_syntax: bool,
+    /// Token ranges of the statements, blocks and expressions, used to build
+    /// the CST
+    syntax_ranges: Vec<(SyntaxKind, Range<usize>)>,
+    /// Labels of the loops enclosing the current statement, up to the nearest
+    /// function
+    loops: Vec<Option<String>>,
 }
 
 impl<'a> Parser<'a> {
@@ -17,23 +64,96 @@ impl<'a> Parser<'a> {
         Self {
             tokens: tokens.iter().peekable(),
             current_token: tokens.first().unwrap(), // assuming existing EOF
+            errors: Vec::new(),
+            after_separator: false,
+            index: 0,
+            previous_end: 0,
+            previous_span: Span::default(),
+            record_syntax: false,
+            syntax_ranges: Vec::new(),
+            loops: Vec::new(),
         }
     }
 
+    /// Parses the tokens and returns the first syntax error, if any.
     pub fn parse(&mut self) -> Result<Vec<Statement>, ParsingError> {
+        let (nodes, errors) = self.parse_with_recovery();
+
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(nodes),
+        }
+    }
+
+    /// Parses the tokens and keeps going after syntax errors. The statements
+    /// that failed are left out of the returned nodes and every error is
+    /// reported.
+    pub fn parse_with_recovery(&mut self) -> (Vec<Statement>, Vec<ParsingError>) {
         self.advance();
 
         let mut nodes: Vec<Statement> = Vec::new();
 
         while !self.current_token.value.is_eof() {
-            let statement = self.parse_statement()?;
-            match &statement {
-                Statement::FunctionDeclaration(_) => nodes.insert(0, statement),
-                _ => nodes.push(statement),
+            let start = self.current_token;
+            let start_index = self.current_index();
+            // exports are only allowed at the top level of a module
+            let statement = match self.current_token.value {
+                TokenType::Export => self.parse_export(),
+                _ => self.parse_statement(),
+            };
+
+            match statement {
+                Ok(statement) => {
+                    nodes.push(statement);
+                    self.mark(SyntaxKind::Statement, start_index);
+                }
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize(start, false);
+                    self.mark(SyntaxKind::Error, start_index);
+                }
             }
         }
 
-        Ok(nodes)
+        (nodes, std::mem::take(&mut self.errors))
+    }
+
+    /// Skips the rest of a statement that failed to parse, up to the start of
+    /// the next one. Inside a block, the closing brace also ends the statement.
+    fn synchronize(&mut self, start: &Token, in_block: bool) {
+        if std::ptr::eq(start, self.current_token) && !self.current_token.value.is_eof() {
+            self.advance();
+        }
+
+        while !self.after_separator && !self.current_token.value.is_eof() {
+            if in_block && self.current_token.value == TokenType::RightBrace {
+                break;
+            }
+
+            self.advance();
+        }
+    }
+
+    fn current_index(&self) -> usize {
+        self.index.saturating_sub(1)
+    }
+
+    /// Records the range of a syntax node going from the token at `start` to
+    /// the last token consumed.
+    fn mark(&mut self, kind: SyntaxKind, start: usize) {
+        if self.record_syntax && self.previous_end > start {
+            self.syntax_ranges.push((kind, start..self.previous_end));
+        }
+    }
+
+    /// The span going from `start` to the end of the last token consumed.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous_span)
+    }
+
+    fn next_token(&mut self) -> &'a Token {
+        self.index += 1;
+        self.tokens.next().unwrap()
     }
 
     fn clone_token(&self) -> Token {
@@ -49,38 +169,76 @@ impl<'a> Parser<'a> {
     }
 
     fn advance(&mut self) {
-        self.current_token = self.tokens.next().unwrap();
+        self.previous_end = self.index;
+        self.previous_span = self.current_token.span;
+        self.current_token = self.next_token();
+        self.after_separator = false;
 
         while self.current_token.value.is_skipable() {
-            self.current_token = self.tokens.next().unwrap();
+            self.current_token = self.next_token();
+            self.after_separator = true;
         }
     }
 
     fn skip_line(&mut self) {
         while self.current_token.value.is_line_break() {
-            self.current_token = self.tokens.next().unwrap();
+            self.current_token = self.next_token();
         }
     }
 
     fn parse_statement(&mut self) -> Result<Statement, ParsingError> {
+        if self.current_token.value.is_identifier() && self.peek().value == TokenType::Colon {
+            return self.parse_labelled_loop();
+        }
+
         let statement = match self.current_token.value {
             TokenType::Set => self.parse_variable_declaration()?,
             TokenType::LeftBrace => self.parse_block()?,
-            TokenType::For => self.parse_for()?,
-            TokenType::While => self.parse_while()?,
-            TokenType::Loop => self.parse_loop()?,
+            TokenType::For => self.parse_for(None)?,
+            TokenType::While => self.parse_while(None)?,
+            TokenType::Loop => self.parse_loop(None)?,
             TokenType::Break => self.parse_break()?,
             TokenType::Continue => self.parse_continue()?,
             TokenType::Function => self.parse_function()?,
             TokenType::Return => self.parse_return()?,
+            TokenType::Try => self.parse_try()?,
             _ => Statement::ExpressionStatement(self.parse_expression()?),
         };
 
         Ok(statement)
     }
 
+    fn parse_export(&mut self) -> Result<Statement, ParsingError> {
+        let token = self.clone_token();
+        self.advance();
+
+        let declaration = match self.current_token.value {
+            TokenType::Set => self.parse_variable_declaration()?,
+            TokenType::Function => self.parse_function()?,
+            _ => {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::ExpectedDeclaration(self.clone_lexeme()),
+                    self.current_token.pos,
+                ))
+            }
+        };
+        let statement = Statement::ExportStatement(Export {
+            span: self.span_from(token.span),
+            token,
+            declaration: Box::new(declaration),
+        });
+
+        Ok(statement)
+    }
+
     fn parse_variable_declaration(&mut self) -> Result<Statement, ParsingError> {
+        let start = self.current_token.span;
         self.advance();
+
+        if self.current_token.value == TokenType::LeftBrace {
+            return self.parse_destructuring(start);
+        }
+
         let name = match &self.current_token.value {
             TokenType::Identifier(_) => self.clone_token(),
             _ => {
@@ -101,7 +259,57 @@ impl<'a> Parser<'a> {
 
         self.advance();
         let value = self.parse_expression()?;
-        let declaration = Statement::VariableDeclaration(Declaration { name, value });
+        let declaration = Statement::VariableDeclaration(Declaration {
+            name,
+            value,
+            span: self.span_from(start),
+        });
+
+        Ok(declaration)
+    }
+
+    fn parse_destructuring(&mut self, start: Span) -> Result<Statement, ParsingError> {
+        self.advance();
+        let mut names = Vec::new();
+
+        while self.current_token.value != TokenType::RightBrace {
+            if !self.current_token.value.is_identifier() {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::ExpectedIdentifier(self.clone_lexeme()),
+                    self.current_token.pos,
+                ));
+            }
+
+            names.push(self.clone_token());
+            self.advance();
+
+            match self.current_token.value {
+                TokenType::Comma => self.advance(),
+                TokenType::RightBrace => {}
+                _ => {
+                    return Err(ParsingError::new(
+                        ParsingErrorKind::ExpectedComma(self.clone_lexeme()),
+                        self.current_token.pos,
+                    ))
+                }
+            }
+        }
+        self.advance();
+
+        if self.current_token.value != TokenType::Equal {
+            return Err(ParsingError::new(
+                ParsingErrorKind::MissingAssignment,
+                self.current_token.pos,
+            ));
+        }
+
+        self.advance();
+        let value = self.parse_expression()?;
+        let declaration = Statement::DestructuringDeclaration(Destructuring {
+            names,
+            value,
+            span: self.span_from(start),
+        });
 
         Ok(declaration)
     }
@@ -120,7 +328,16 @@ impl<'a> Parser<'a> {
         }
 
         if let Some(token) = next_three.next() {
-            if token.value == TokenType::Colon || token.value == TokenType::Comma {
+            // a labelled loop also starts with a name and a colon
+            let is_label = token.value == TokenType::Colon
+                && next_three.next().is_some_and(|token| {
+                    matches!(
+                        token.value,
+                        TokenType::For | TokenType::While | TokenType::Loop
+                    )
+                });
+
+            if !is_label && (token.value == TokenType::Colon || token.value == TokenType::Comma) {
                 return Ok(Statement::ExpressionStatement(self.parse_expression()?));
             }
         }
@@ -132,6 +349,8 @@ impl<'a> Parser<'a> {
             ));
         }
 
+        let block_start = self.current_index();
+        let start_span = self.current_token.span;
         self.advance();
 
         let mut statements: Vec<Statement> = vec![];
@@ -143,19 +362,76 @@ impl<'a> Parser<'a> {
                 ));
             }
 
-            let statement = self.parse_statement()?;
-            match &statement {
-                Statement::FunctionDeclaration(_) => statements.insert(0, statement),
-                _ => statements.push(statement),
+            let start = self.current_token;
+            let start_index = self.current_index();
+
+            match self.parse_statement() {
+                Ok(statement) => {
+                    statements.push(statement);
+                    self.mark(SyntaxKind::Statement, start_index);
+                }
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize(start, true);
+                    self.mark(SyntaxKind::Error, start_index);
+                }
             }
         }
-        let statement = Statement::BlockStatement(Block { statements });
         self.advance();
+        let statement = Statement::BlockStatement(Block {
+            statements,
+            span: self.span_from(start_span),
+        });
+        self.mark(SyntaxKind::Block, block_start);
 
         Ok(statement)
     }
 
-    fn parse_for(&mut self) -> Result<Statement, ParsingError> {
+    /// Parses a loop preceded by a label, `outer: for ...`.
+    fn parse_labelled_loop(&mut self) -> Result<Statement, ParsingError> {
+        let label = self.clone_token();
+        self.advance();
+        self.advance();
+
+        match self.current_token.value {
+            TokenType::For => self.parse_for(Some(label)),
+            TokenType::While => self.parse_while(Some(label)),
+            TokenType::Loop => self.parse_loop(Some(label)),
+            _ => Err(ParsingError::new(
+                ParsingErrorKind::ExpectedLoop(self.clone_lexeme()),
+                self.current_token.pos,
+            )),
+        }
+    }
+
+    /// Parses the block of a loop, `break` and `continue` can target it from
+    /// inside.
+    fn parse_loop_body(&mut self, label: &Option<Token>) -> Result<Box<Statement>, ParsingError> {
+        self.loops
+            .push(label.as_ref().map(|label| label.lexeme.clone()));
+        let body = self.parse_block();
+        self.loops.pop();
+
+        Ok(Box::new(body?))
+    }
+
+    /// Parses the body of a function, the loops around it can't be targeted
+    /// from inside.
+    fn parse_function_body(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Statement, ParsingError>,
+    ) -> Result<Box<Statement>, ParsingError> {
+        let loops = std::mem::take(&mut self.loops);
+        let body = parse(self);
+        self.loops = loops;
+
+        Ok(Box::new(body?))
+    }
+
+    fn parse_for(&mut self, label: Option<Token>) -> Result<Statement, ParsingError> {
+        let start = label
+            .as_ref()
+            .map_or(self.current_token.span, |label| label.span);
         self.advance();
         let key = if self.current_token.value.is_identifier() {
             self.clone_token()
@@ -198,19 +474,24 @@ impl<'a> Parser<'a> {
             ));
         }
 
-        let block = Box::new(self.parse_block()?);
+        let block = self.parse_loop_body(&label)?;
 
-        let for_statement = Statement::ForStatement(For {
+        let for_statement = Statement::ForStatement(Box::new(For {
+            label,
             variable: (key, value),
             iterable,
             iterable_token,
             block,
-        });
+            span: self.span_from(start),
+        }));
 
         Ok(for_statement)
     }
 
-    fn parse_while(&mut self) -> Result<Statement, ParsingError> {
+    fn parse_while(&mut self, label: Option<Token>) -> Result<Statement, ParsingError> {
+        let start = label
+            .as_ref()
+            .map_or(self.current_token.span, |label| label.span);
         self.advance();
         let condition = self.parse_expression()?;
 
@@ -221,13 +502,21 @@ impl<'a> Parser<'a> {
             ));
         }
 
-        let block = Box::new(self.parse_block()?);
-        let statement = Statement::WhileStatement(While { condition, block });
+        let block = self.parse_loop_body(&label)?;
+        let statement = Statement::WhileStatement(While {
+            label,
+            condition,
+            block,
+            span: self.span_from(start),
+        });
 
         Ok(statement)
     }
 
-    fn parse_loop(&mut self) -> Result<Statement, ParsingError> {
+    fn parse_loop(&mut self, label: Option<Token>) -> Result<Statement, ParsingError> {
+        let start = label
+            .as_ref()
+            .map_or(self.current_token.span, |label| label.span);
         self.advance();
 
         if self.current_token.value != TokenType::LeftBrace {
@@ -237,29 +526,69 @@ impl<'a> Parser<'a> {
             ));
         }
 
-        let block = Box::new(self.parse_block()?);
-        let statement = Statement::LoopStatement(Loop { block });
+        let block = self.parse_loop_body(&label)?;
+        let statement = Statement::LoopStatement(Loop {
+            label,
+            block,
+            span: self.span_from(start),
+        });
 
         Ok(statement)
     }
 
     fn parse_break(&mut self) -> Result<Statement, ParsingError> {
-        let token = self.clone_token();
-        let statement = Statement::BreakStatement(Break { token });
-        self.advance();
+        let (token, label) = self.parse_jump()?;
+        let statement = Statement::BreakStatement(Break {
+            span: self.span_from(token.span),
+            token,
+            label,
+        });
 
         Ok(statement)
     }
 
     fn parse_continue(&mut self) -> Result<Statement, ParsingError> {
+        let (token, label) = self.parse_jump()?;
+        let statement = Statement::ContinueStatement(Continue {
+            span: self.span_from(token.span),
+            token,
+            label,
+        });
+
+        Ok(statement)
+    }
+
+    /// Parses the keyword of a `break` or `continue` and the label following it
+    /// on the same line, which must name an enclosing loop.
+    fn parse_jump(&mut self) -> Result<(Token, Option<Token>), ParsingError> {
         let token = self.clone_token();
-        let statement = Statement::ContinueStatement(Continue { token });
+
+        if self.loops.is_empty() {
+            return Err(ParsingError::new(
+                ParsingErrorKind::OutsideLoop(token.lexeme),
+                token.pos,
+            ));
+        }
         self.advance();
 
-        Ok(statement)
+        if self.after_separator || !self.current_token.value.is_identifier() {
+            return Ok((token, None));
+        }
+
+        let label = self.clone_token();
+        if !self.loops.contains(&Some(label.lexeme.clone())) {
+            return Err(ParsingError::new(
+                ParsingErrorKind::UndeclaredLabel(label.lexeme),
+                label.pos,
+            ));
+        }
+        self.advance();
+
+        Ok((token, Some(label)))
     }
 
     fn parse_function(&mut self) -> Result<Statement, ParsingError> {
+        let start = self.current_token.span;
         self.advance();
         let token = Some(self.clone_token());
 
@@ -279,11 +608,12 @@ impl<'a> Parser<'a> {
             ));
         }
 
-        let body = Box::new(self.parse_block()?);
+        let body = self.parse_function_body(Self::parse_block)?;
         let declaration = Statement::FunctionDeclaration(FunctionDeclaration {
             token,
             parameter,
             body,
+            span: self.span_from(start),
         });
 
         Ok(declaration)
@@ -293,7 +623,76 @@ impl<'a> Parser<'a> {
         let token = self.clone_token();
         self.advance();
         let expression = self.parse_expression()?;
-        let statement = Statement::ReturnStatement(Return { token, expression });
+        let statement = Statement::ReturnStatement(Return {
+            span: self.span_from(token.span),
+            token,
+            expression,
+        });
+
+        Ok(statement)
+    }
+
+    fn parse_try(&mut self) -> Result<Statement, ParsingError> {
+        let token = self.clone_token();
+        self.advance();
+
+        if self.current_token.value != TokenType::LeftBrace {
+            return Err(ParsingError::new(
+                ParsingErrorKind::ExpectedLeftBrace(self.clone_lexeme()),
+                self.current_token.pos,
+            ));
+        }
+
+        let block = Box::new(self.parse_block()?);
+
+        if self.current_token.value != TokenType::Catch {
+            return Err(ParsingError::new(
+                ParsingErrorKind::ExpectedCatch(self.clone_lexeme()),
+                self.current_token.pos,
+            ));
+        }
+        self.advance();
+
+        if self.current_token.value != TokenType::LeftParenthesis {
+            return Err(ParsingError::new(
+                ParsingErrorKind::ExpectedLeftParenthesis(self.clone_lexeme()),
+                self.current_token.pos,
+            ));
+        }
+        self.advance();
+
+        if !self.current_token.value.is_identifier() {
+            return Err(ParsingError::new(
+                ParsingErrorKind::ExpectedIdentifier(self.clone_lexeme()),
+                self.current_token.pos,
+            ));
+        }
+        let error = self.clone_token();
+        self.advance();
+
+        if self.current_token.value != TokenType::RighParenethesis {
+            return Err(ParsingError::new(
+                ParsingErrorKind::MissingClosingParenthesis,
+                self.current_token.pos,
+            ));
+        }
+        self.advance();
+
+        if self.current_token.value != TokenType::LeftBrace {
+            return Err(ParsingError::new(
+                ParsingErrorKind::ExpectedLeftBrace(self.clone_lexeme()),
+                self.current_token.pos,
+            ));
+        }
+
+        let handler = Box::new(self.parse_block()?);
+        let statement = Statement::TryStatement(Try {
+            span: self.span_from(token.span),
+            token,
+            block,
+            error,
+            handler,
+        });
 
         Ok(statement)
     }
@@ -303,8 +702,9 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_assignment(&mut self) -> Result<Expression, ParsingError> {
+        let start_index = self.current_index();
         let target_token = self.clone_token();
-        let expression = self.parse_or()?;
+        let expression = self.parse_binary(0)?;
 
         if self.current_token.value.is_assignment() {
             let token = self.clone_token();
@@ -319,7 +719,9 @@ impl<'a> Parser<'a> {
                     left: Box::new(expression),
                     token,
                     value: Box::new(value),
+                    span: self.span_from(target_token.span),
                 });
+                self.mark(SyntaxKind::Assignment, start_index);
                 return Ok(assignment);
             }
 
@@ -332,62 +734,18 @@ impl<'a> Parser<'a> {
         Ok(expression)
     }
 
-    fn parse_or(&mut self) -> Result<Expression, ParsingError> {
-        let expression = self.parse_and()?;
-
-        if self.current_token.value.is_or() {
-            let operator = self.clone_token();
-            self.advance();
-
-            if self.current_token.value.is_eof() {
-                return Err(ParsingError::new(
-                    ParsingErrorKind::MissingRightOperand(operator.lexeme),
-                    operator.pos,
-                ));
-            }
-
-            let right = self.parse_or()?;
-
-            return Ok(Expression::BinaryExpression(Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            }));
-        }
-
-        Ok(expression)
-    }
-
-    fn parse_and(&mut self) -> Result<Expression, ParsingError> {
-        let expression = self.parse_eqality()?;
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expression, ParsingError> {
+        let start = self.current_token.span;
+        let start_index = self.current_index();
+        let mut expression = self.parse_unary()?;
 
-        if self.current_token.value.is_and() {
-            let operator = self.clone_token();
-            self.advance();
-
-            if self.current_token.value.is_eof() {
-                return Err(ParsingError::new(
-                    ParsingErrorKind::MissingRightOperand(operator.lexeme),
-                    operator.pos,
-                ));
+        while let Some((precedence, associativity)) =
+            get_binary_precedence(&self.current_token.value)
+        {
+            if precedence < min_precedence {
+                break;
             }
 
-            let right = self.parse_and()?;
-
-            return Ok(Expression::BinaryExpression(Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            }));
-        }
-
-        Ok(expression)
-    }
-
-    fn parse_eqality(&mut self) -> Result<Expression, ParsingError> {
-        let expression = self.parse_comparaison()?;
-
-        if self.current_token.value.is_equality() {
             let operator = self.clone_token();
             self.advance();
 
@@ -398,117 +756,26 @@ impl<'a> Parser<'a> {
                 ));
             }
 
-            let right = self.parse_eqality()?;
+            let right = self.parse_binary(precedence + 1)?;
 
-            return Ok(Expression::BinaryExpression(Binary {
+            expression = Expression::BinaryExpression(Binary {
                 left: Box::new(expression),
                 operator,
                 right: Box::new(right),
-            }));
-        }
+                span: self.span_from(start),
+            });
+            self.mark(SyntaxKind::Binary, start_index);
 
-        Ok(expression)
-    }
-
-    fn parse_comparaison(&mut self) -> Result<Expression, ParsingError> {
-        let expression = self.parse_range()?;
-
-        if self.current_token.value.is_comparaison() {
-            let operator = self.clone_token();
-            self.advance();
-
-            if self.current_token.value.is_eof() {
-                return Err(ParsingError::new(
-                    ParsingErrorKind::MissingRightOperand(operator.lexeme),
-                    operator.pos,
-                ));
-            }
-
-            let right = self.parse_comparaison()?;
-
-            return Ok(Expression::BinaryExpression(Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            }));
-        }
-
-        Ok(expression)
-    }
-
-    fn parse_range(&mut self) -> Result<Expression, ParsingError> {
-        let expression = self.parse_term()?;
-
-        if self.current_token.value.is_range() {
-            let operator = self.clone_token();
-            self.advance();
-
-            if self.current_token.value.is_eof() {
-                return Err(ParsingError::new(
-                    ParsingErrorKind::MissingRightOperand(operator.lexeme),
-                    operator.pos,
-                ));
-            }
-
-            let right = self.parse_term()?;
-
-            return Ok(Expression::BinaryExpression(Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            }));
-        }
-
-        Ok(expression)
-    }
-
-    fn parse_term(&mut self) -> Result<Expression, ParsingError> {
-        let expression = self.parse_factor()?;
-
-        if self.current_token.value.is_term_op() {
-            let operator = self.clone_token();
-            self.advance();
-
-            if self.current_token.value.is_eof() {
-                return Err(ParsingError::new(
-                    ParsingErrorKind::MissingRightOperand(operator.lexeme),
-                    operator.pos,
-                ));
-            }
-
-            let right = self.parse_term()?;
-
-            return Ok(Expression::BinaryExpression(Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            }));
-        }
-
-        Ok(expression)
-    }
-
-    fn parse_factor(&mut self) -> Result<Expression, ParsingError> {
-        let expression = self.parse_unary()?;
-
-        if self.current_token.value.is_factor_op() {
-            let operator = self.clone_token();
-            self.advance();
-
-            if self.current_token.value.is_eof() {
-                return Err(ParsingError::new(
-                    ParsingErrorKind::MissingRightOperand(operator.lexeme),
-                    operator.pos,
-                ));
+            if associativity == Associativity::None {
+                if let Some((next, _)) = get_binary_precedence(&self.current_token.value) {
+                    if next == precedence {
+                        return Err(ParsingError::new(
+                            ParsingErrorKind::UnexpectedToken(self.clone_lexeme()),
+                            self.current_token.pos,
+                        ));
+                    }
+                }
             }
-
-            let right = self.parse_factor()?;
-
-            return Ok(Expression::BinaryExpression(Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            }));
         }
 
         Ok(expression)
@@ -516,17 +783,24 @@ impl<'a> Parser<'a> {
 
     fn parse_unary(&mut self) -> Result<Expression, ParsingError> {
         if self.current_token.value.is_unary() {
+            let start_index = self.current_index();
             let operator = self.clone_token();
             self.advance();
             let operand = Box::new(self.parse_unary()?);
+            self.mark(SyntaxKind::Unary, start_index);
 
-            return Ok(Expression::UnaryExpression(Unary { operator, operand }));
+            return Ok(Expression::UnaryExpression(Unary {
+                span: self.span_from(operator.span),
+                operator,
+                operand,
+            }));
         }
 
         self.parse_primary()
     }
 
     fn parse_primary(&mut self) -> Result<Expression, ParsingError> {
+        let start_index = self.current_index();
         let token = self.clone_token();
         let mut expression = match token.value {
             TokenType::Eof => {
@@ -562,10 +836,15 @@ impl<'a> Parser<'a> {
                             self.current_token.pos,
                         ));
                     }
-                    Expression::LiteralExpression(Literal {
-                        token: token.clone(),
-                    })
-                } else if self.current_token.value.is_binary_operator() {
+                    match &token.value {
+                        TokenType::InterpolatedString(parts) => {
+                            self.parse_interpolation(&token, parts)?
+                        }
+                        _ => Expression::LiteralExpression(Literal {
+                            token: token.clone(),
+                        }),
+                    }
+                } else if get_binary_precedence(&self.current_token.value).is_some() {
                     return Err(ParsingError::new(
                         ParsingErrorKind::MissingLeftOperand(token.lexeme),
                         self.current_token.pos,
@@ -587,11 +866,26 @@ impl<'a> Parser<'a> {
             self.advance();
         }
 
+        let kind = match &token.value {
+            TokenType::If => SyntaxKind::If,
+            TokenType::Match => SyntaxKind::Match,
+            TokenType::Lambda => SyntaxKind::Lambda,
+            TokenType::LeftParenthesis => SyntaxKind::Group,
+            TokenType::LeftBracket => SyntaxKind::Array,
+            TokenType::LeftBrace => SyntaxKind::Object,
+            TokenType::Identifier(_) => SyntaxKind::Identifier,
+            TokenType::InterpolatedString(_) => SyntaxKind::Interpolation,
+            _ => SyntaxKind::Literal,
+        };
+        self.mark(kind, start_index);
+
         loop {
             expression = match self.current_token.value {
-                TokenType::LeftBracket => self.parse_index(expression)?,
-                TokenType::LeftParenthesis => self.parse_call(expression, None)?,
-                TokenType::Dot => self.parse_prop_access(expression)?,
+                TokenType::LeftBracket => self.parse_index(expression, token.span, start_index)?,
+                TokenType::LeftParenthesis => {
+                    self.parse_call(expression, None, token.span, start_index)?
+                }
+                TokenType::Dot => self.parse_prop_access(expression, token.span, start_index)?,
                 _ => break,
             }
         }
@@ -614,6 +908,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_array(&mut self) -> Result<Expression, ParsingError> {
+        let start = self.current_token.span;
         self.advance();
         let mut items: Vec<Expression> = vec![];
 
@@ -641,12 +936,52 @@ impl<'a> Parser<'a> {
                 self.advance();
             }
         }
-        let array_expression = Expression::ArrayExpression(Array { items });
+        // the closing bracket is consumed by `parse_primary`
+        let array_expression = Expression::ArrayExpression(Array {
+            items,
+            span: start.to(self.current_token.span),
+        });
 
         Ok(array_expression)
     }
 
+    fn parse_interpolation(
+        &self,
+        token: &Token,
+        parts: &[StringPart],
+    ) -> Result<Expression, ParsingError> {
+        let mut interpolation = Interpolation {
+            token: token.clone(),
+            parts: Vec::with_capacity(parts.len()),
+        };
+
+        for part in parts {
+            let part = match part {
+                StringPart::Literal(value) => InterpolationPart::Literal(value.clone()),
+                StringPart::Expression(tokens) => {
+                    let mut parser = Parser::new(tokens);
+                    parser.advance();
+                    let expression = parser.parse_expression()?;
+
+                    if !parser.current_token.value.is_eof() {
+                        return Err(ParsingError::new(
+                            ParsingErrorKind::UnexpectedToken(parser.clone_lexeme()),
+                            parser.current_token.pos,
+                        ));
+                    }
+
+                    InterpolationPart::Expression(expression)
+                }
+            };
+
+            interpolation.parts.push(part);
+        }
+
+        Ok(Expression::InterpolatedString(interpolation))
+    }
+
     fn parse_object(&mut self) -> Result<Expression, ParsingError> {
+        let start = self.current_token.span;
         self.advance();
         let mut props: Vec<(Token, Expression)> = vec![];
 
@@ -658,7 +993,10 @@ impl<'a> Parser<'a> {
                 ));
             }
 
-            if self.current_token.value.is_symbol() || self.current_token.lexeme.contains('.') {
+            if self.current_token.value.is_symbol()
+                || self.current_token.lexeme.contains('.')
+                || matches!(self.current_token.value, TokenType::InterpolatedString(_))
+            {
                 return Err(ParsingError::new(
                     ParsingErrorKind::InvalidProp(self.clone_lexeme()),
                     self.current_token.pos,
@@ -702,12 +1040,20 @@ impl<'a> Parser<'a> {
                 self.advance()
             }
         }
-        let object_expression = Expression::ObjectExpression(Object { props });
+        let object_expression = Expression::ObjectExpression(Object {
+            props,
+            span: start.to(self.current_token.span),
+        });
 
         Ok(object_expression)
     }
 
-    fn parse_index(&mut self, expression: Expression) -> Result<Expression, ParsingError> {
+    fn parse_index(
+        &mut self,
+        expression: Expression,
+        start: Span,
+        start_index: usize,
+    ) -> Result<Expression, ParsingError> {
         let token = self.clone_token();
         self.advance();
         let index = Box::new(self.parse_expression()?);
@@ -724,12 +1070,19 @@ impl<'a> Parser<'a> {
             token,
             expression: Box::new(expression),
             index,
+            span: self.span_from(start),
         });
+        self.mark(SyntaxKind::Index, start_index);
 
         Ok(index_expression)
     }
 
-    fn parse_prop_access(&mut self, expression: Expression) -> Result<Expression, ParsingError> {
+    fn parse_prop_access(
+        &mut self,
+        expression: Expression,
+        start: Span,
+        start_index: usize,
+    ) -> Result<Expression, ParsingError> {
         let token = self.clone_token();
         self.advance();
 
@@ -747,10 +1100,12 @@ impl<'a> Parser<'a> {
             token,
             expression: Box::new(expression.clone()),
             prop,
+            span: self.span_from(start),
         });
+        self.mark(SyntaxKind::Property, start_index);
 
         if self.current_token.value == TokenType::LeftParenthesis {
-            let call = self.parse_call(prop_access, Some(expression))?;
+            let call = self.parse_call(prop_access, Some(expression), start, start_index)?;
             return Ok(call);
         }
 
@@ -758,6 +1113,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_if(&mut self) -> Result<Expression, ParsingError> {
+        let start = self.current_token.span;
         self.advance();
         let condition = Box::new(self.parse_expression()?);
 
@@ -790,12 +1146,14 @@ impl<'a> Parser<'a> {
             condition,
             true_branch,
             else_branch,
+            span: self.span_from(start),
         });
 
         Ok(if_exression)
     }
 
     fn parse_match(&mut self) -> Result<Expression, ParsingError> {
+        let start = self.current_token.span;
         self.advance();
         let pattern = Box::new(self.parse_expression()?);
 
@@ -838,12 +1196,14 @@ impl<'a> Parser<'a> {
             pattern,
             arms,
             default,
+            span: self.span_from(start),
         });
 
         Ok(expression)
     }
 
     fn parse_match_arm(&mut self) -> Result<MatchArm, ParsingError> {
+        let start = self.current_token.span;
         let mut pattern: Vec<Box<Expression>> = vec![];
         loop {
             let expr = match &self.current_token.value {
@@ -882,7 +1242,11 @@ impl<'a> Parser<'a> {
 
         self.advance();
         let block = Box::new(self.parse_statement()?);
-        let arm = MatchArm { pattern, block };
+        let arm = MatchArm {
+            pattern,
+            block,
+            span: self.span_from(start),
+        };
 
         Ok(arm)
     }
@@ -891,7 +1255,10 @@ impl<'a> Parser<'a> {
         &mut self,
         expression: Expression,
         object: Option<Expression>,
+        start: Span,
+        start_index: usize,
     ) -> Result<Expression, ParsingError> {
+        let arguments_index = self.current_index();
         let token = self.clone_token();
         self.advance();
         let mut arguments: Vec<Expression> = vec![];
@@ -911,6 +1278,7 @@ impl<'a> Parser<'a> {
             }
         }
         self.advance();
+        self.mark(SyntaxKind::ArgumentList, arguments_index);
 
         let object = object.map(Box::new);
         let call = Expression::FunctionCall(Call {
@@ -918,15 +1286,22 @@ impl<'a> Parser<'a> {
             caller: Box::new(expression),
             object,
             arguments,
+            span: self.span_from(start),
         });
+        self.mark(SyntaxKind::Call, start_index);
 
         Ok(call)
     }
 
     fn parse_lambda(&mut self) -> Result<Expression, ParsingError> {
+        let start = self.current_token.span;
         let parameter = self.get_function_param()?;
-        let body = Box::new(self.parse_statement()?);
-        let lambda = Expression::LambdaFunction(Lambda { parameter, body });
+        let body = self.parse_function_body(Self::parse_statement)?;
+        let lambda = Expression::LambdaFunction(Lambda {
+            parameter,
+            body,
+            span: self.span_from(start),
+        });
 
         Ok(lambda)
     }
@@ -939,6 +1314,7 @@ impl<'a> Parser<'a> {
                 self.current_token.pos,
             ));
         }
+        let start_index = self.current_index();
         self.advance();
 
         let mut parameter: Vec<Token> = vec![];
@@ -979,14 +1355,20 @@ impl<'a> Parser<'a> {
         }
 
         self.advance();
+        self.mark(SyntaxKind::ParameterList, start_index);
+
         Ok(parameter)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Parser;
-    use lexer::Lexer;
+    use super::{
+        ast::{Expression, InterpolationPart, Statement},
+        cst::{self, SyntaxKind},
+        Parser, ParsingErrorKind,
+    };
+    use lexer::{utils::Span, Lexer};
 
     #[test]
     fn test_precedence() {
@@ -1012,6 +1394,43 @@ mod test {
         assert_eq!(node.to_string(), exptected);
     }
 
+    #[test]
+    fn test_associativity() {
+        let cases = [
+            ("10 - 4 - 3", "((10 - 4) - 3)"),
+            ("8 / 4 / 2", "((8 / 4) / 2)"),
+            ("2 * 3 % 4 / 5", "(((2 * 3) % 4) / 5)"),
+            ("1 + 2 % 3 - 4", "((1 + (2 % 3)) - 4)"),
+            ("a < b <= c", "((a < b) <= c)"),
+            ("a == b != c", "((a == b) != c)"),
+            ("a or b or c and d", "((a or b) or (c and d))"),
+            ("-a * b - -c", "(((-a) * b) - (-c))"),
+            ("0 to n - 1", "(0 to (n - 1))"),
+            ("a < 1 to 5 == b", "((a < (1 to 5)) == b)"),
+            ("x = y = 1 + 2 + 3", "x = y = ((1 + 2) + 3)"),
+        ];
+
+        for (expr, expected) in cases {
+            let tokens = Lexer::new(expr).tokenize().unwrap();
+            let ast = Parser::new(&tokens).parse().unwrap();
+            let node = ast.first().unwrap();
+            assert_eq!(node.to_string(), expected);
+        }
+
+        let tokens = Lexer::new("1 to 2 to 3").tokenize().unwrap();
+        assert!(Parser::new(&tokens).parse().is_err());
+
+        for operator in ["*", "%", "==", "and", "to"] {
+            let expr = format!("{operator} 2");
+            let tokens = Lexer::new(&expr).tokenize().unwrap();
+            let error = Parser::new(&tokens).parse().unwrap_err();
+            assert_eq!(
+                error.kind,
+                ParsingErrorKind::MissingLeftOperand(operator.to_owned())
+            );
+        }
+    }
+
     #[test]
     fn test_declaration() {
         let stmt = "set a = 2;";
@@ -1109,6 +1528,55 @@ mod test {
         assert_eq!(node.to_string(), expected);
     }
 
+    #[test]
+    fn test_labelled_loop() {
+        let stmt = "
+            outer: for i in 0 to 3 {
+                inner: while true {
+                    if (i == 1) { continue outer }
+                    break
+                }
+            }
+        ";
+        let expected =
+            "outer: for i in (0 to 3) { inner: while (true) { if ((i == 1)) { continue outer; }; break; }; }";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let node = ast.first().unwrap();
+        assert_eq!(node.to_string(), expected);
+        assert_eq!(node.span(), Span::new(13, 182));
+    }
+
+    #[test]
+    fn test_jump_outside_loop() {
+        let source = "
+            break
+            loop {
+                set f = lambda() { continue }
+                break outer
+            }
+            outer: print(1)
+            loop { function g() { loop { break } } break }
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let (ast, errors) = Parser::new(&tokens).parse_with_recovery();
+        let errors: Vec<(ParsingErrorKind, u32)> = errors
+            .into_iter()
+            .map(|error| (error.kind, error.position.line_start))
+            .collect();
+
+        assert_eq!(ast.len(), 2);
+        assert_eq!(
+            errors,
+            [
+                (ParsingErrorKind::OutsideLoop("break".to_owned()), 1),
+                (ParsingErrorKind::OutsideLoop("continue".to_owned()), 3),
+                (ParsingErrorKind::UndeclaredLabel("outer".to_owned()), 4),
+                (ParsingErrorKind::ExpectedLoop("print".to_owned()), 6),
+            ]
+        );
+    }
+
     #[test]
     fn test_match() {
         let stmt = "
@@ -1238,4 +1706,273 @@ mod test {
         let node = ast.first().unwrap();
         assert_eq!(node.to_string(), expected);
     }
+
+    #[test]
+    fn test_try() {
+        let stmt = "
+            try {
+                throw(\"error\")
+            } catch (err) {
+                print(err)
+            }
+        ";
+        let expected = "try { throw(\"error\"); } catch (err) { print(err); }";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let node = ast.first().unwrap();
+        assert_eq!(node.to_string(), expected);
+
+        let stmt = "try { a } finally { b }";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        assert!(Parser::new(&tokens).parse().is_err());
+    }
+
+    #[test]
+    fn test_export() {
+        let stmt = "
+            export set a = 1;
+            export function add(a, b) { a + b }
+            set { a, add } = import('module');
+        ";
+        let expected = [
+            "export set a = 1",
+            "export function add(a, b) { (a + b); }",
+            "set { a, add } = import('module')",
+        ];
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        for (node, expected) in ast.iter().zip(expected) {
+            assert_eq!(node.to_string(), expected);
+        }
+
+        let stmt = "export 1";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        let error = Parser::new(&tokens).parse().unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParsingErrorKind::ExpectedDeclaration("1".to_owned())
+        );
+
+        let stmt = "if true { export set a = 1 }";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        assert!(Parser::new(&tokens).parse().is_err());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let stmt = "print('Hello {name}, { length(items) } items')";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let node = ast.first().unwrap();
+        assert_eq!(node.to_string(), stmt);
+
+        let stmt = "'{a b}'";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        let error = Parser::new(&tokens).parse().unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParsingErrorKind::UnexpectedToken("b".to_owned())
+        );
+        assert_eq!(error.position.col_start, 2);
+
+        let stmt = "{ 'a{b}': 1 }";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        assert!(Parser::new(&tokens).parse().is_err());
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let stmt = "set a = [0xFF, 0b1010, 1e-9, 1_000.5]";
+        let tokens = Lexer::new(stmt).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let node = ast.first().unwrap();
+        assert_eq!(node.to_string(), stmt);
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let source = "
+            set a = 1 +;
+            set b = 2
+            function f() {
+                set = 3
+                return b
+            }
+            set c = [1, 2
+            set d = (4; }
+            print(d)
+        ";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let (ast, errors) = Parser::new(&tokens).parse_with_recovery();
+        let statements: Vec<String> = ast.iter().map(|node| node.to_string()).collect();
+        let errors: Vec<(ParsingErrorKind, u32)> = errors
+            .into_iter()
+            .map(|error| (error.kind, error.position.line_start))
+            .collect();
+
+        assert_eq!(
+            statements,
+            ["set b = 2", "function f() { return b; }", "print(d)"]
+        );
+        assert_eq!(
+            errors,
+            [
+                (ParsingErrorKind::UnexpectedToken("set".to_owned()), 2),
+                (ParsingErrorKind::ExpectedIdentifier("=".to_owned()), 4),
+                (ParsingErrorKind::ExpectedComma("set".to_owned()), 8),
+                (ParsingErrorKind::MissingClosingParenthesis, 8),
+                (ParsingErrorKind::UnexpectedToken("}".to_owned()), 8),
+            ]
+        );
+
+        let tokens = Lexer::new("set = 1; set b = ;").tokenize().unwrap();
+        let error = Parser::new(&tokens).parse().unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParsingErrorKind::ExpectedIdentifier("=".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_cst() {
+        let source = "-- header\nset a = 1\n\nfunction f(x) {\n    <-- body --> return x + a\n    set = 2\n}\nprint(f(2)) -- call\n";
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+        let (program, errors) = cst::parse(tokens);
+
+        assert_eq!(program.text(), source);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            program.nodes().map(|node| node.kind).collect::<Vec<_>>(),
+            [SyntaxKind::Statement; 3]
+        );
+
+        let function = program.nodes().nth(1).unwrap();
+        assert_eq!(
+            function.nodes().map(|node| node.kind).collect::<Vec<_>>(),
+            [SyntaxKind::ParameterList, SyntaxKind::Block]
+        );
+        assert_eq!(function.nodes().next().unwrap().text(), "(x)");
+
+        let block = function.nodes().nth(1).unwrap();
+        assert_eq!(
+            block.nodes().map(|node| node.kind).collect::<Vec<_>>(),
+            [SyntaxKind::Statement, SyntaxKind::Error]
+        );
+        assert_eq!(
+            block.nodes().next().unwrap().text(),
+            "    <-- body --> return x + a"
+        );
+
+        let source = "set a = 1 -- one\n{ print(a) }\nexport function f() { return [1,\n 2] }\n";
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+        let (program, errors) = cst::parse(tokens);
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let mut parser = Parser::new(&tokens);
+        let expected = parser.parse().unwrap();
+
+        assert!(parser.syntax_ranges.is_empty());
+
+        assert!(errors.is_empty());
+        assert_eq!(program.text(), source);
+
+        let derived = program.to_ast().unwrap();
+        assert_eq!(derived.len(), expected.len());
+
+        for ((node, derived), expected) in program.nodes().zip(derived).zip(expected) {
+            assert_eq!(derived.to_string(), expected.to_string());
+            assert_eq!(node.to_ast().unwrap()[0].to_string(), expected.to_string());
+        }
+    }
+
+    #[test]
+    fn test_cst_expressions() {
+        fn outline(node: &cst::SyntaxNode) -> String {
+            let children: Vec<_> = node.nodes().map(outline).collect();
+
+            match children.is_empty() {
+                true => format!("{:?}", node.kind),
+                false => format!("{:?}({})", node.kind, children.join(" ")),
+            }
+        }
+
+        let source = "a.b[0] = -f(1, 'x') + (2 * [3][0])\nprint(lambda(x) { x }, { k: \"{v}\" })";
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+        let (program, errors) = cst::parse(tokens);
+
+        assert!(errors.is_empty());
+        assert_eq!(program.text(), source);
+
+        let statements: Vec<_> = program.nodes().map(outline).collect();
+        assert_eq!(
+            statements,
+            [
+                "Statement(Assignment(Index(Property(Identifier) Literal) \
+                 Binary(Unary(Call(Identifier ArgumentList(Literal Literal))) \
+                 Group(Binary(Literal Index(Array(Literal) Literal))))))",
+                "Statement(Call(Identifier ArgumentList(\
+                 Lambda(ParameterList Block(Statement(Identifier))) Object(Interpolation))))",
+            ]
+        );
+
+        let call = program.nodes().nth(1).unwrap().nodes().next().unwrap();
+        let arguments = call.nodes().nth(1).unwrap();
+        assert_eq!(arguments.text(), "(lambda(x) { x }, { k: \"{v}\" })");
+
+        let source = "f(1, +)\nset a = [1, (2 * x.y(\n";
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+        let (program, errors) = cst::parse(tokens);
+        let statements: Vec<_> = program.nodes().map(outline).collect();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(program.text(), source);
+        assert_eq!(
+            statements,
+            [
+                "Error(Identifier Literal)",
+                "Error(Literal Literal Property(Identifier))"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spans() {
+        let source = "set a = [1, (2 + 3) * 4]\nfunction f(x) { return x.y[0](1) }\nif (a) { 1 } else { 2 }\nprint('= {-a.b}')";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let text = |span: Span| &source[span.start..span.end];
+
+        assert_eq!(text(ast[0].span()), "set a = [1, (2 + 3) * 4]");
+        assert_eq!(text(ast[1].span()), "function f(x) { return x.y[0](1) }");
+        assert_eq!(text(ast[2].span()), "if (a) { 1 } else { 2 }");
+
+        let Statement::VariableDeclaration(declaration) = &ast[0] else {
+            panic!("expected a declaration");
+        };
+        let Expression::ArrayExpression(array) = &declaration.value else {
+            panic!("expected an array");
+        };
+        assert_eq!(text(array.span), "[1, (2 + 3) * 4]");
+        assert_eq!(text(array.items[1].span()), "(2 + 3) * 4");
+
+        let Statement::FunctionDeclaration(function) = &ast[1] else {
+            panic!("expected a function");
+        };
+        let Statement::BlockStatement(block) = function.body.as_ref() else {
+            panic!("expected a block");
+        };
+        assert_eq!(text(block.span), "{ return x.y[0](1) }");
+        assert_eq!(text(block.statements[0].span()), "return x.y[0](1)");
+
+        let Statement::ExpressionStatement(Expression::FunctionCall(call)) = &ast[3] else {
+            panic!("expected a call");
+        };
+        let Expression::InterpolatedString(interpolation) = &call.arguments[0] else {
+            panic!("expected an interpolated string");
+        };
+        let InterpolationPart::Expression(expression) = &interpolation.parts[1] else {
+            panic!("expected an expression");
+        };
+        assert_eq!(text(call.span), "print('= {-a.b}')");
+        assert_eq!(text(expression.span()), "-a.b");
+    }
 }
@@ -1,12 +1,15 @@
-use cli::{print_errror, repl_mode};
+use cli::{
+    diagnostics::{print_diagnostic, print_runtime_error, Diagnostic},
+    print_errror, repl_mode,
+};
 use interpreter::{
     builtin::{get_io_builtins, get_std_builtins},
-    error::RuntimeErrorKind,
     Interpreter,
 };
 use lexer::Lexer;
 use parser::Parser;
 use std::{env, fs::read_to_string, path::Path, process};
+use vm::Vm;
 
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
@@ -14,6 +17,7 @@ fn main() {
     match args.len() {
         0 => repl_mode(),
         1 => run_file(Path::new(&args[0])),
+        2 if args[0] == "--vm" => run_file_vm(Path::new(&args[1])),
         _ => eprintln!("Invalid number of arguments"),
     }
 }
@@ -26,27 +30,58 @@ fn run_file(file_path: &Path) {
 
     let path = file_path.parent().unwrap().to_path_buf();
     let interpreter = Interpreter::new(path);
+    interpreter.set_main_module(file_path);
     interpreter.load_builtin(get_std_builtins());
     interpreter.load_builtin(get_io_builtins());
 
+    let name = file_path.display().to_string();
     let tokens = Lexer::new(&contents).tokenize().unwrap_or_else(|err| {
-        print_errror("Parsing error", err);
+        print_diagnostic(&Diagnostic::from(&err), &name, &contents);
+        process::exit(1)
+    });
+    let (nodes, errors) = Parser::new(&tokens).parse_with_recovery();
+
+    if !errors.is_empty() {
+        for err in errors {
+            print_diagnostic(&Diagnostic::from(&err), &name, &contents);
+        }
         process::exit(1)
+    }
+
+    if let Some(error) = interpreter.interpret_program(&nodes).err() {
+        print_runtime_error(&error, &name, &contents);
+        process::exit(1)
+    }
+}
+
+fn run_file_vm(file_path: &Path) {
+    let contents = read_to_string(file_path).unwrap_or_else(|err| {
+        print_errror("Internal error", err);
+        process::exit(1);
     });
-    let nodes = Parser::new(&tokens).parse().unwrap_or_else(|err| {
-        print_errror("Syntax error", err);
+
+    let path = file_path.parent().unwrap().to_path_buf();
+    let mut vm = Vm::new(path);
+    vm.set_main_module(file_path);
+    vm.load_builtin(get_std_builtins());
+    vm.load_builtin(get_io_builtins());
+
+    let name = file_path.display().to_string();
+    let tokens = Lexer::new(&contents).tokenize().unwrap_or_else(|err| {
+        print_diagnostic(&Diagnostic::from(&err), &name, &contents);
         process::exit(1)
     });
+    let (nodes, errors) = Parser::new(&tokens).parse_with_recovery();
 
-    for node in nodes {
-        if let Some(error) = interpreter.interpret(node).err() {
-            match error.kind {
-                RuntimeErrorKind::Export(_) => {}
-                _ => {
-                    print_errror("Runtime error", error);
-                    process::exit(1)
-                }
-            }
+    if !errors.is_empty() {
+        for err in errors {
+            print_diagnostic(&Diagnostic::from(&err), &name, &contents);
         }
+        process::exit(1)
+    }
+
+    if let Some(error) = vm.interpret(&nodes).err() {
+        print_runtime_error(&error, &name, &contents);
+        process::exit(1)
     }
 }
@@ -0,0 +1,206 @@
+use interpreter::value::Value;
+use lexer::{tokens::TokenType, utils::Position};
+use std::{mem, path::PathBuf, rc::Rc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Substract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Range,
+    Negate,
+    Not,
+    Assign,
+    AddAssign,
+    SubstractAssign,
+    MultiplyAssign,
+    DivideAssign,
+    ModuloAssign,
+}
+
+impl Operator {
+    pub fn from_token(token: &TokenType) -> Self {
+        match token {
+            TokenType::Plus => Operator::Add,
+            TokenType::Minus => Operator::Substract,
+            TokenType::Asterix => Operator::Multiply,
+            TokenType::Slash => Operator::Divide,
+            TokenType::Modulo => Operator::Modulo,
+            TokenType::EqualEqual => Operator::Equal,
+            TokenType::BangEqual => Operator::NotEqual,
+            TokenType::Greater => Operator::Greater,
+            TokenType::GreaterEqual => Operator::GreaterEqual,
+            TokenType::Less => Operator::Less,
+            TokenType::LessEqual => Operator::LessEqual,
+            TokenType::To => Operator::Range,
+            TokenType::Bang => Operator::Not,
+            TokenType::Equal => Operator::Assign,
+            TokenType::PlusEqual => Operator::AddAssign,
+            TokenType::MinusEqaul => Operator::SubstractAssign,
+            TokenType::AsterixEqual => Operator::MultiplyAssign,
+            TokenType::SlashEqual => Operator::DivideAssign,
+            TokenType::ModuloEqual => Operator::ModuloAssign,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn to_token(self) -> TokenType {
+        match self {
+            Operator::Add => TokenType::Plus,
+            Operator::Substract | Operator::Negate => TokenType::Minus,
+            Operator::Multiply => TokenType::Asterix,
+            Operator::Divide => TokenType::Slash,
+            Operator::Modulo => TokenType::Modulo,
+            Operator::Equal => TokenType::EqualEqual,
+            Operator::NotEqual => TokenType::BangEqual,
+            Operator::Greater => TokenType::Greater,
+            Operator::GreaterEqual => TokenType::GreaterEqual,
+            Operator::Less => TokenType::Less,
+            Operator::LessEqual => TokenType::LessEqual,
+            Operator::Range => TokenType::To,
+            Operator::Not => TokenType::Bang,
+            Operator::Assign => TokenType::Equal,
+            Operator::AddAssign => TokenType::PlusEqual,
+            Operator::SubstractAssign => TokenType::MinusEqaul,
+            Operator::MultiplyAssign => TokenType::AsterixEqual,
+            Operator::DivideAssign => TokenType::SlashEqual,
+            Operator::ModuloAssign => TokenType::ModuloEqual,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    /// Pushes a value from the constant pool
+    Constant(usize),
+    Null,
+    Pop,
+    Dup,
+
+    /// Globals are looked up by name, the operand is an index in the name table
+    DefineGlobal(usize),
+    GetGlobal(usize),
+    /// Locals live in resolved slots of the current frame
+    DefineLocal(usize),
+    GetLocal(usize),
+    SetLocal(usize),
+    GetUpvalue(usize),
+
+    /// Places are pushed on the reference stack to be assigned with `Store`
+    RefGlobal(usize),
+    RefLocal(usize),
+    RefUpvalue(usize),
+    RefIndex,
+    RefProperty(usize),
+    Store(Operator),
+
+    Unary(Operator),
+    Binary(Operator),
+
+    Array(usize),
+    Object(usize),
+    /// Joins the displayed values of an interpolated string
+    Concat(usize),
+    GetIndex,
+    GetProperty(usize),
+    /// Same as `GetProperty` but a name missing from a module is an error
+    GetMember(usize),
+
+    /// Creates a closure from a prototype of the function table
+    Closure(usize),
+    Call(usize),
+    /// Same as `Call` but the value below the callee is bound to `self`
+    CallMethod(usize),
+    Return,
+
+    Jump(usize),
+    JumpIfFalse(usize),
+
+    /// Loops record the stack height so `break` and `continue` can unwind it,
+    /// `Unwind` first leaves the given number of inner loops
+    EnterLoop,
+    ExitLoop,
+    Unwind(usize),
+
+    Iterate,
+    /// Pushes the next (key and) value of the current iterator or jumps when
+    /// it is exhausted
+    Next(usize, bool),
+    EndIterate,
+
+    /// Registers the handler to jump to when an error is raised
+    EnterTry(usize),
+    ExitTry,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capture {
+    Local(usize),
+    Upvalue(usize),
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<Instruction>,
+    pub positions: Vec<Position>,
+    pub constants: Vec<Value>,
+    pub names: Vec<String>,
+    pub functions: Vec<Rc<Prototype>>,
+}
+
+impl Chunk {
+    pub fn emit(&mut self, instruction: Instruction, position: Position) -> usize {
+        self.code.push(instruction);
+        self.positions.push(position);
+        self.code.len() - 1
+    }
+
+    pub fn add_constant(&mut self, value: Value) -> usize {
+        match self
+            .constants
+            .iter()
+            // numbers of different kinds, and decimals with different digits
+            // like 0.1 and 0.10, are equal when their values are
+            .position(|constant| match (constant, &value) {
+                (Value::Decimal(a), Value::Decimal(b)) => a.scale() == b.scale() && a == b,
+                _ => mem::discriminant(constant) == mem::discriminant(&value) && *constant == value,
+            }) {
+            Some(index) => index,
+            None => {
+                self.constants.push(value);
+                self.constants.len() - 1
+            }
+        }
+    }
+
+    pub fn add_name(&mut self, name: &str) -> usize {
+        match self.names.iter().position(|value| value == name) {
+            Some(index) => index,
+            None => {
+                self.names.push(name.to_owned());
+                self.names.len() - 1
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Prototype {
+    pub name: Option<String>,
+    pub arity: usize,
+    pub locals: usize,
+    pub upvalues: Vec<Capture>,
+    pub chunk: Chunk,
+    /// Globals exported by a script
+    pub exports: Vec<String>,
+    /// File of the module the function was compiled from
+    pub file: PathBuf,
+}
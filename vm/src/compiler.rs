@@ -0,0 +1,863 @@
+use crate::chunk::{Capture, Instruction, Operator, Prototype};
+
+use interpreter::{
+    error::{RuntimeError, RuntimeErrorKind},
+    value::Value,
+};
+use lexer::{
+    tokens::{Token, TokenType},
+    utils::Position,
+};
+use parser::{ast::*, error::ParsingErrorKind};
+use std::path::PathBuf;
+
+enum Resolved {
+    Local(usize),
+    Upvalue(usize),
+    Global(usize),
+}
+
+struct Local {
+    name: String,
+    depth: usize,
+}
+
+struct LoopScope {
+    label: Option<String>,
+    start: usize,
+    breaks: Vec<usize>,
+}
+
+struct FunctionState {
+    prototype: Prototype,
+    locals: Vec<Local>,
+    depth: usize,
+    loops: Vec<LoopScope>,
+    is_script: bool,
+}
+
+impl FunctionState {
+    fn new(name: Option<String>, is_script: bool) -> Self {
+        Self {
+            prototype: Prototype {
+                name,
+                ..Default::default()
+            },
+            locals: Vec::new(),
+            depth: 0,
+            loops: Vec::new(),
+            is_script,
+        }
+    }
+
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.locals.iter().rposition(|local| local.name == name)
+    }
+}
+
+/// Compiles the AST into function prototypes. Top-level declarations of the
+/// script are globals, everything else is resolved to a local slot or to an
+/// upvalue captured from an enclosing function.
+pub struct Compiler {
+    states: Vec<FunctionState>,
+    position: Position,
+    file: PathBuf,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            states: vec![FunctionState::new(None, true)],
+            position: Position::new(0, 0, 0, 0),
+            file: PathBuf::new(),
+        }
+    }
+
+    /// Creates a compiler for the module at `file`, the compiled functions
+    /// keep it for their errors.
+    pub fn with_file(file: PathBuf) -> Self {
+        Self {
+            file,
+            ..Self::new()
+        }
+    }
+
+    pub fn compile(mut self, statements: &[Statement]) -> Result<Prototype, RuntimeError> {
+        self.hoist_functions(statements)?;
+
+        for statement in statements {
+            self.compile_statement(statement)?;
+        }
+
+        self.emit(Instruction::Null);
+        self.emit(Instruction::Return);
+
+        let mut state = self.states.pop().unwrap();
+        state.prototype.file = self.file.clone();
+
+        Ok(state.prototype)
+    }
+
+    fn state(&mut self) -> &mut FunctionState {
+        self.states.last_mut().unwrap()
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        let position = self.position;
+        self.state().prototype.chunk.emit(instruction, position)
+    }
+
+    fn current_offset(&mut self) -> usize {
+        self.state().prototype.chunk.code.len()
+    }
+
+    fn patch_jump(&mut self, offset: usize) {
+        let target = self.current_offset();
+        let code = &mut self.state().prototype.chunk.code;
+
+        code[offset] = match code[offset] {
+            Instruction::Jump(_) => Instruction::Jump(target),
+            Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(target),
+            Instruction::Next(_, keyed) => Instruction::Next(target, keyed),
+            Instruction::EnterTry(_) => Instruction::EnterTry(target),
+            _ => unreachable!(),
+        };
+    }
+
+    fn emit_constant(&mut self, value: Value) {
+        let index = self.state().prototype.chunk.add_constant(value);
+        self.emit(Instruction::Constant(index));
+    }
+
+    fn add_name(&mut self, name: &str) -> usize {
+        self.state().prototype.chunk.add_name(name)
+    }
+
+    fn begin_scope(&mut self) {
+        self.state().depth += 1;
+    }
+
+    fn end_scope(&mut self) {
+        let state = self.state();
+        state.depth -= 1;
+
+        while let Some(local) = state.locals.last() {
+            if local.depth <= state.depth {
+                break;
+            }
+            state.locals.pop();
+        }
+    }
+
+    fn is_global_scope(&self) -> bool {
+        let state = self.states.last().unwrap();
+        state.is_script && state.depth == 0
+    }
+
+    fn declare_local(&mut self, name: &str, position: Position) -> Result<usize, RuntimeError> {
+        let state = self.state();
+        let redeclared = state
+            .locals
+            .iter()
+            .rev()
+            .take_while(|local| local.depth == state.depth)
+            .any(|local| local.name == name);
+
+        if redeclared {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::RedeclaringIdentifier(name.to_owned()),
+                position,
+            ));
+        }
+
+        let depth = state.depth;
+        state.locals.push(Local {
+            name: name.to_owned(),
+            depth,
+        });
+
+        let slot = state.locals.len() - 1;
+        state.prototype.locals = state.prototype.locals.max(slot + 1);
+
+        Ok(slot)
+    }
+
+    fn add_upvalue(&mut self, level: usize, capture: Capture) -> usize {
+        let upvalues = &mut self.states[level].prototype.upvalues;
+
+        match upvalues.iter().position(|upvalue| *upvalue == capture) {
+            Some(index) => index,
+            None => {
+                upvalues.push(capture);
+                upvalues.len() - 1
+            }
+        }
+    }
+
+    fn resolve_upvalue(&mut self, level: usize, name: &str) -> Option<usize> {
+        if level == 0 {
+            return None;
+        }
+
+        if let Some(slot) = self.states[level - 1].resolve_local(name) {
+            return Some(self.add_upvalue(level, Capture::Local(slot)));
+        }
+
+        let index = self.resolve_upvalue(level - 1, name)?;
+
+        Some(self.add_upvalue(level, Capture::Upvalue(index)))
+    }
+
+    fn resolve(&mut self, name: &str) -> Resolved {
+        let level = self.states.len() - 1;
+
+        if let Some(slot) = self.states[level].resolve_local(name) {
+            Resolved::Local(slot)
+        } else if let Some(index) = self.resolve_upvalue(level, name) {
+            Resolved::Upvalue(index)
+        } else {
+            Resolved::Global(self.add_name(name))
+        }
+    }
+
+    fn compile_statement(&mut self, statement: &Statement) -> Result<(), RuntimeError> {
+        match statement {
+            Statement::ExpressionStatement(expression) => {
+                self.compile_expression(expression)?;
+                self.emit(Instruction::Pop);
+            }
+            Statement::VariableDeclaration(declaration) => {
+                self.compile_declaration(declaration)?;
+            }
+            Statement::DestructuringDeclaration(declaration) => {
+                self.compile_destructuring(declaration)?;
+            }
+            Statement::ExportStatement(export) => {
+                self.compile_statement(&export.declaration)?;
+
+                for name in export.get_names() {
+                    self.state().prototype.exports.push(name.lexeme.clone());
+                }
+            }
+            // already compiled by `hoist_functions`
+            Statement::FunctionDeclaration(_) => {}
+            Statement::BlockStatement(block) => {
+                self.begin_scope();
+                self.hoist_functions(&block.statements)?;
+                for statement in &block.statements {
+                    self.compile_statement(statement)?;
+                }
+                self.end_scope();
+            }
+            Statement::WhileStatement(statement) => self.compile_while(statement)?,
+            Statement::LoopStatement(statement) => self.compile_loop(statement)?,
+            Statement::ForStatement(statement) => self.compile_for(statement)?,
+            Statement::TryStatement(statement) => {
+                self.compile_try(statement)?;
+                self.emit(Instruction::Pop);
+            }
+            Statement::BreakStatement(statement) => {
+                self.position = statement.token.pos;
+
+                let index = self.resolve_loop(&statement.token, &statement.label)?;
+                let exited = self.state().loops.len() - 1 - index;
+                self.emit(Instruction::Unwind(exited));
+                let jump = self.emit(Instruction::Jump(0));
+                self.state().loops[index].breaks.push(jump);
+            }
+            Statement::ContinueStatement(statement) => {
+                self.position = statement.token.pos;
+
+                let index = self.resolve_loop(&statement.token, &statement.label)?;
+                let exited = self.state().loops.len() - 1 - index;
+                let start = self.state().loops[index].start;
+                self.emit(Instruction::Unwind(exited));
+                self.emit(Instruction::Jump(start));
+            }
+            // a top-level return ends the script like its last statement would
+            Statement::ReturnStatement(statement) => {
+                self.compile_expression(&statement.expression)?;
+                self.position = statement.token.pos;
+                self.emit(Instruction::Return);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compiles a statement so that it leaves exactly one value on the stack,
+    /// which is how blocks are used as expressions.
+    fn compile_value(&mut self, statement: &Statement) -> Result<(), RuntimeError> {
+        match statement {
+            Statement::ExpressionStatement(expression) => self.compile_expression(expression)?,
+            Statement::BlockStatement(block) => {
+                self.begin_scope();
+                self.hoist_functions(&block.statements)?;
+
+                match block.statements.split_last() {
+                    Some((last, statements)) => {
+                        for statement in statements {
+                            self.compile_statement(statement)?;
+                        }
+                        self.compile_value(last)?;
+                    }
+                    None => {
+                        self.emit(Instruction::Null);
+                    }
+                }
+
+                self.end_scope();
+            }
+            Statement::TryStatement(statement) => self.compile_try(statement)?,
+            _ => {
+                self.compile_statement(statement)?;
+                self.emit(Instruction::Null);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_declaration(&mut self, declaration: &Declaration) -> Result<(), RuntimeError> {
+        let name = &declaration.name;
+
+        if self.is_global_scope() {
+            self.compile_expression(&declaration.value)?;
+            self.position = name.pos;
+            let index = self.add_name(&name.lexeme);
+            self.emit(Instruction::DefineGlobal(index));
+
+            return Ok(());
+        }
+
+        // lambdas are bound before being created so that they can call themselves
+        if let Expression::LambdaFunction(_) = &declaration.value {
+            let slot = self.declare_local(&name.lexeme, name.pos)?;
+            self.emit(Instruction::Null);
+            self.emit(Instruction::DefineLocal(slot));
+            self.compile_expression(&declaration.value)?;
+            self.emit(Instruction::SetLocal(slot));
+        } else {
+            self.compile_expression(&declaration.value)?;
+            let slot = self.declare_local(&name.lexeme, name.pos)?;
+            self.emit(Instruction::DefineLocal(slot));
+        }
+
+        Ok(())
+    }
+
+    /// Binds the value on top of the stack to a new variable.
+    fn define_variable(&mut self, name: &Token) -> Result<(), RuntimeError> {
+        self.position = name.pos;
+
+        if self.is_global_scope() {
+            let index = self.add_name(&name.lexeme);
+            self.emit(Instruction::DefineGlobal(index));
+        } else {
+            let slot = self.declare_local(&name.lexeme, name.pos)?;
+            self.emit(Instruction::DefineLocal(slot));
+        }
+
+        Ok(())
+    }
+
+    fn compile_destructuring(&mut self, declaration: &Destructuring) -> Result<(), RuntimeError> {
+        self.compile_expression(&declaration.value)?;
+
+        for name in &declaration.names {
+            self.position = name.pos;
+            let index = self.add_name(&name.lexeme);
+            self.emit(Instruction::Dup);
+            self.emit(Instruction::GetMember(index));
+            self.define_variable(name)?;
+        }
+
+        self.emit(Instruction::Pop);
+
+        Ok(())
+    }
+
+    /// Compiles the function declarations of a block before its other
+    /// statements. Local functions are all declared first so that they can
+    /// reference each other.
+    fn hoist_functions(&mut self, statements: &[Statement]) -> Result<(), RuntimeError> {
+        let declarations: Vec<_> = statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::FunctionDeclaration(declaration) => Some(declaration),
+                Statement::ExportStatement(export) => match export.declaration.as_ref() {
+                    Statement::FunctionDeclaration(declaration) => Some(declaration),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        let is_global = self.is_global_scope();
+        let mut slots = Vec::new();
+
+        if !is_global {
+            for declaration in &declarations {
+                let token = declaration.token.as_ref().unwrap();
+                self.position = token.pos;
+                let slot = self.declare_local(&token.lexeme, token.pos)?;
+                self.emit(Instruction::Null);
+                self.emit(Instruction::DefineLocal(slot));
+                slots.push(slot);
+            }
+        }
+
+        for (index, declaration) in declarations.into_iter().enumerate() {
+            let token = declaration.token.as_ref().unwrap();
+            self.position = token.pos;
+            self.compile_function(
+                Some(&token.lexeme),
+                &declaration.parameter,
+                &declaration.body,
+            )?;
+
+            if is_global {
+                let index = self.add_name(&token.lexeme);
+                self.emit(Instruction::DefineGlobal(index));
+            } else {
+                self.emit(Instruction::SetLocal(slots[index]));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_function(
+        &mut self,
+        name: Option<&str>,
+        parameter: &[lexer::tokens::Token],
+        body: &Statement,
+    ) -> Result<(), RuntimeError> {
+        let mut state = FunctionState::new(name.map(str::to_owned), false);
+        state.prototype.arity = parameter.len();
+        self.states.push(state);
+        self.begin_scope();
+
+        // slot 0 holds the receiver of method calls
+        self.declare_local("self", self.position)?;
+        for param in parameter {
+            self.state().locals.push(Local {
+                name: param.lexeme.clone(),
+                depth: 1,
+            });
+        }
+        let state = self.state();
+        state.prototype.locals = state.prototype.locals.max(state.locals.len());
+
+        self.compile_value(body)?;
+        self.emit(Instruction::Return);
+
+        let mut state = self.states.pop().unwrap();
+        state.prototype.file = self.file.clone();
+        let chunk = &mut self.state().prototype.chunk;
+        chunk.functions.push(state.prototype.into());
+        let index = chunk.functions.len() - 1;
+        self.emit(Instruction::Closure(index));
+
+        Ok(())
+    }
+
+    /// Finds the loop targeted by a `break` or `continue`, the innermost one
+    /// when there is no label.
+    fn resolve_loop(
+        &mut self,
+        token: &Token,
+        label: &Option<Token>,
+    ) -> Result<usize, RuntimeError> {
+        let loops = &self.state().loops;
+        let index = match label {
+            Some(label) => loops
+                .iter()
+                .rposition(|scope| scope.label.as_ref() == Some(&label.lexeme)),
+            None => loops.len().checked_sub(1),
+        };
+
+        index.ok_or_else(|| {
+            let kind = match label {
+                Some(label) => ParsingErrorKind::UndeclaredLabel(label.lexeme.clone()),
+                None => ParsingErrorKind::OutsideLoop(token.lexeme.clone()),
+            };
+
+            RuntimeError::new(RuntimeErrorKind::ParsingError(kind), token.pos)
+        })
+    }
+
+    fn compile_loop_body(
+        &mut self,
+        label: &Option<Token>,
+        start: usize,
+        body: impl FnOnce(&mut Self) -> Result<(), RuntimeError>,
+    ) -> Result<Vec<usize>, RuntimeError> {
+        self.state().loops.push(LoopScope {
+            label: label.as_ref().map(|label| label.lexeme.clone()),
+            start,
+            breaks: Vec::new(),
+        });
+        body(self)?;
+        self.emit(Instruction::Jump(start));
+
+        Ok(self.state().loops.pop().unwrap().breaks)
+    }
+
+    fn compile_while(&mut self, statement: &While) -> Result<(), RuntimeError> {
+        self.emit(Instruction::EnterLoop);
+        let start = self.current_offset();
+        self.compile_expression(&statement.condition)?;
+        let exit = self.emit(Instruction::JumpIfFalse(0));
+
+        let breaks = self.compile_loop_body(&statement.label, start, |compiler| {
+            compiler.compile_statement(&statement.block)
+        })?;
+
+        self.patch_jump(exit);
+        for jump in breaks {
+            self.patch_jump(jump);
+        }
+        self.emit(Instruction::ExitLoop);
+
+        Ok(())
+    }
+
+    fn compile_loop(&mut self, statement: &Loop) -> Result<(), RuntimeError> {
+        self.emit(Instruction::EnterLoop);
+        let start = self.current_offset();
+
+        let breaks = self.compile_loop_body(&statement.label, start, |compiler| {
+            compiler.compile_statement(&statement.block)
+        })?;
+
+        for jump in breaks {
+            self.patch_jump(jump);
+        }
+        self.emit(Instruction::ExitLoop);
+
+        Ok(())
+    }
+
+    fn compile_for(&mut self, statement: &For) -> Result<(), RuntimeError> {
+        self.compile_expression(&statement.iterable)?;
+        self.position = statement.iterable_token.pos;
+        self.emit(Instruction::Iterate);
+        self.emit(Instruction::EnterLoop);
+
+        let start = self.current_offset();
+        let (first, second) = &statement.variable;
+        let next = self.emit(Instruction::Next(0, second.is_some()));
+
+        let breaks = self.compile_loop_body(&statement.label, start, |compiler| {
+            compiler.begin_scope();
+
+            // each iteration gets fresh bindings so that closures capture the current one
+            match second {
+                Some(second) => {
+                    let key = compiler.declare_local(&first.lexeme, first.pos)?;
+                    let value = compiler.declare_local(&second.lexeme, second.pos)?;
+                    compiler.emit(Instruction::DefineLocal(value));
+                    compiler.emit(Instruction::DefineLocal(key));
+                }
+                None => {
+                    let value = compiler.declare_local(&first.lexeme, first.pos)?;
+                    compiler.emit(Instruction::DefineLocal(value));
+                }
+            }
+
+            compiler.compile_statement(&statement.block)?;
+            compiler.end_scope();
+
+            Ok(())
+        })?;
+
+        self.patch_jump(next);
+        for jump in breaks {
+            self.patch_jump(jump);
+        }
+        self.emit(Instruction::ExitLoop);
+        self.emit(Instruction::EndIterate);
+
+        Ok(())
+    }
+
+    /// Leaves the value of the block, or of the handler if an error was caught.
+    fn compile_try(&mut self, statement: &Try) -> Result<(), RuntimeError> {
+        self.position = statement.token.pos;
+        let enter = self.emit(Instruction::EnterTry(0));
+        self.compile_value(&statement.block)?;
+        self.emit(Instruction::ExitTry);
+        let jump = self.emit(Instruction::Jump(0));
+
+        self.patch_jump(enter);
+        self.begin_scope();
+        let error = &statement.error;
+        let slot = self.declare_local(&error.lexeme, error.pos)?;
+        self.emit(Instruction::DefineLocal(slot));
+        self.compile_value(&statement.handler)?;
+        self.end_scope();
+        self.patch_jump(jump);
+
+        Ok(())
+    }
+
+    fn compile_expression(&mut self, expression: &Expression) -> Result<(), RuntimeError> {
+        match expression {
+            Expression::LiteralExpression(literal) => {
+                self.position = literal.token.pos;
+
+                match &literal.token.value {
+                    TokenType::Number(value) => self.emit_constant(Value::Number(*value)),
+                    TokenType::Integer(value) => self.emit_constant(Value::Integer(*value)),
+                    TokenType::BigInt(value) => self.emit_constant(Value::BigInt(value.clone())),
+                    TokenType::Decimal(value) => self.emit_constant(Value::Decimal(*value)),
+                    TokenType::String(value) => self.emit_constant(Value::String(value.clone())),
+                    TokenType::True => self.emit_constant(Value::Boolean(true)),
+                    TokenType::False => self.emit_constant(Value::Boolean(false)),
+                    TokenType::Null => {
+                        self.emit(Instruction::Null);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            Expression::InterpolatedString(interpolation) => {
+                for part in &interpolation.parts {
+                    match part {
+                        InterpolationPart::Literal(value) => {
+                            self.emit_constant(Value::String(value.clone()))
+                        }
+                        InterpolationPart::Expression(expression) => {
+                            self.compile_expression(expression)?
+                        }
+                    }
+                }
+                self.position = interpolation.token.pos;
+                self.emit(Instruction::Concat(interpolation.parts.len()));
+            }
+            Expression::VariableExpression(variable) => {
+                self.position = variable.token.pos;
+
+                let instruction = match self.resolve(&variable.token.lexeme) {
+                    Resolved::Local(slot) => Instruction::GetLocal(slot),
+                    Resolved::Upvalue(index) => Instruction::GetUpvalue(index),
+                    Resolved::Global(index) => Instruction::GetGlobal(index),
+                };
+                self.emit(instruction);
+            }
+            Expression::AssignementExpression(assign) => {
+                self.compile_expression(&assign.value)?;
+                self.compile_place(&assign.left)?;
+                self.position = assign.token.pos;
+                self.emit(Instruction::Store(Operator::from_token(
+                    &assign.token.value,
+                )));
+            }
+            Expression::ArrayExpression(array) => {
+                for item in &array.items {
+                    self.compile_expression(item)?;
+                }
+                self.emit(Instruction::Array(array.items.len()));
+            }
+            Expression::ObjectExpression(object) => {
+                for (token, expression) in &object.props {
+                    let name = match &token.value {
+                        TokenType::String(value) => value,
+                        _ => &token.lexeme,
+                    };
+                    self.emit_constant(Value::String(name.to_owned()));
+                    self.compile_expression(expression)?;
+                }
+                self.emit(Instruction::Object(object.props.len()));
+            }
+            Expression::IndexExpression(index) => {
+                self.compile_expression(&index.expression)?;
+                self.compile_expression(&index.index)?;
+                self.position = index.token.pos;
+                self.emit(Instruction::GetIndex);
+            }
+            Expression::PropAccess(access) => {
+                self.compile_expression(&access.expression)?;
+                self.position = access.token.pos;
+                let name = self.add_name(&access.prop.lexeme);
+                self.emit(Instruction::GetProperty(name));
+            }
+            Expression::UnaryExpression(unary) => {
+                self.compile_expression(&unary.operand)?;
+                self.position = unary.operator.pos;
+                let operator = match unary.operator.value {
+                    TokenType::Minus => Operator::Negate,
+                    _ => Operator::Not,
+                };
+                self.emit(Instruction::Unary(operator));
+            }
+            Expression::BinaryExpression(binary) if binary.operator.value.is_and() => {
+                self.compile_expression(&binary.left)?;
+                self.emit(Instruction::Dup);
+                let end = self.emit(Instruction::JumpIfFalse(0));
+                self.emit(Instruction::Pop);
+                self.compile_expression(&binary.right)?;
+                self.patch_jump(end);
+            }
+            Expression::BinaryExpression(binary) if binary.operator.value.is_or() => {
+                self.compile_expression(&binary.left)?;
+                self.emit(Instruction::Dup);
+                let right = self.emit(Instruction::JumpIfFalse(0));
+                let end = self.emit(Instruction::Jump(0));
+                self.patch_jump(right);
+                self.emit(Instruction::Pop);
+                self.compile_expression(&binary.right)?;
+                self.patch_jump(end);
+            }
+            Expression::BinaryExpression(binary) => {
+                self.compile_expression(&binary.left)?;
+                self.compile_expression(&binary.right)?;
+                self.position = binary.operator.pos;
+                self.emit(Instruction::Binary(Operator::from_token(
+                    &binary.operator.value,
+                )));
+            }
+            Expression::IfExpression(expression) => self.compile_if(expression)?,
+            Expression::MatchExpression(expression) => self.compile_match(expression)?,
+            Expression::FunctionCall(call) => self.compile_call(call)?,
+            Expression::LambdaFunction(lambda) => {
+                self.compile_function(None, &lambda.parameter, &lambda.body)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_place(&mut self, expression: &Expression) -> Result<(), RuntimeError> {
+        match expression {
+            Expression::VariableExpression(variable) => {
+                self.position = variable.token.pos;
+
+                let instruction = match self.resolve(&variable.token.lexeme) {
+                    Resolved::Local(slot) => Instruction::RefLocal(slot),
+                    Resolved::Upvalue(index) => Instruction::RefUpvalue(index),
+                    Resolved::Global(index) => Instruction::RefGlobal(index),
+                };
+                self.emit(instruction);
+            }
+            Expression::IndexExpression(index) => {
+                self.compile_place(&index.expression)?;
+                self.compile_expression(&index.index)?;
+                self.position = index.token.pos;
+                self.emit(Instruction::RefIndex);
+            }
+            Expression::PropAccess(access) => {
+                self.compile_place(&access.expression)?;
+                self.position = access.token.pos;
+                let name = self.add_name(&access.prop.lexeme);
+                self.emit(Instruction::RefProperty(name));
+            }
+            _ => {
+                return Err(RuntimeError::new(
+                    RuntimeErrorKind::InvalidAssignment,
+                    self.position,
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_if(&mut self, expression: &If) -> Result<(), RuntimeError> {
+        self.compile_expression(&expression.condition)?;
+        let else_jump = self.emit(Instruction::JumpIfFalse(0));
+        self.compile_value(&expression.true_branch)?;
+        let end_jump = self.emit(Instruction::Jump(0));
+
+        self.patch_jump(else_jump);
+        match &expression.else_branch {
+            Some(branch) => self.compile_value(branch)?,
+            None => {
+                self.emit(Instruction::Null);
+            }
+        }
+        self.patch_jump(end_jump);
+
+        Ok(())
+    }
+
+    fn compile_match(&mut self, expression: &Match) -> Result<(), RuntimeError> {
+        self.compile_expression(&expression.pattern)?;
+        let mut end_jumps = vec![];
+
+        for arm in &expression.arms {
+            let mut body_jumps = vec![];
+
+            for pattern in &arm.pattern {
+                self.emit(Instruction::Dup);
+                self.compile_expression(pattern)?;
+                self.emit(Instruction::Binary(Operator::Equal));
+                let next_pattern = self.emit(Instruction::JumpIfFalse(0));
+                body_jumps.push(self.emit(Instruction::Jump(0)));
+                self.patch_jump(next_pattern);
+            }
+
+            let next_arm = self.emit(Instruction::Jump(0));
+
+            for jump in body_jumps {
+                self.patch_jump(jump);
+            }
+            self.emit(Instruction::Pop);
+            self.compile_value(&arm.block)?;
+            end_jumps.push(self.emit(Instruction::Jump(0)));
+            self.patch_jump(next_arm);
+        }
+
+        self.emit(Instruction::Pop);
+        match &expression.default {
+            Some(default) => self.compile_value(&default.block)?,
+            None => {
+                self.emit(Instruction::Null);
+            }
+        }
+
+        for jump in end_jumps {
+            self.patch_jump(jump);
+        }
+
+        Ok(())
+    }
+
+    fn compile_call(&mut self, call: &Call) -> Result<(), RuntimeError> {
+        let argc = call.arguments.len();
+
+        match (&call.object, &*call.caller) {
+            (Some(object), Expression::PropAccess(access)) => {
+                self.compile_expression(object)?;
+                self.emit(Instruction::Dup);
+                self.position = access.token.pos;
+                let name = self.add_name(&access.prop.lexeme);
+                self.emit(Instruction::GetProperty(name));
+
+                for argument in &call.arguments {
+                    self.compile_expression(argument)?;
+                }
+
+                self.position = call.token.pos;
+                self.emit(Instruction::CallMethod(argc));
+            }
+            _ => {
+                self.compile_expression(&call.caller)?;
+
+                for argument in &call.arguments {
+                    self.compile_expression(argument)?;
+                }
+
+                self.position = call.token.pos;
+                self.emit(Instruction::Call(argc));
+            }
+        }
+
+        Ok(())
+    }
+}
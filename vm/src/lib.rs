@@ -0,0 +1,1228 @@
+pub mod chunk;
+pub mod compiler;
+
+use chunk::{Capture, Instruction, Prototype};
+use compiler::Compiler;
+
+use interpreter::{
+    builtin::{get_io_builtins, get_std_builtins, Builtin, CallContext, Caller},
+    error::{Frame as CallFrame, RuntimeError, RuntimeErrorKind},
+    module::RefModules,
+    operation::*,
+    value::{Compiled, Function, RefVal, Value},
+};
+use lexer::{utils::Position, Lexer};
+use parser::{ast::Statement, Parser};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    vec,
+};
+
+type RefGlobals = Rc<RefCell<HashMap<String, RefVal>>>;
+
+pub struct Closure {
+    pub prototype: Rc<Prototype>,
+    pub upvalues: Vec<RefVal>,
+    /// Globals of the module the closure was created in, a function exported
+    /// by a module keeps using them once imported
+    globals: RefGlobals,
+}
+
+struct Frame {
+    closure: Rc<Closure>,
+    ip: usize,
+    slots: Vec<RefVal>,
+    stack_base: usize,
+    refs_base: usize,
+    iterators_base: usize,
+    /// Stack, handler and iterator heights at the start of each enclosing loop
+    loops: Vec<(usize, usize, usize)>,
+}
+
+impl Frame {
+    fn new(
+        closure: Rc<Closure>,
+        stack_base: usize,
+        refs_base: usize,
+        iterators_base: usize,
+    ) -> Self {
+        let slots = (0..closure.prototype.locals)
+            .map(|_| Rc::new(RefCell::new(Value::Null)))
+            .collect();
+
+        Self {
+            closure,
+            ip: 0,
+            slots,
+            stack_base,
+            refs_base,
+            iterators_base,
+            loops: Vec::new(),
+        }
+    }
+}
+
+/// State to restore when an error is caught by a `try` block.
+struct Handler {
+    target: usize,
+    frames: usize,
+    stack: usize,
+    refs: usize,
+    iterators: usize,
+    loops: usize,
+}
+
+/// Stack-based virtual machine executing the prototypes produced by the
+/// compiler. It shares its values, builtins and errors with the tree-walking
+/// interpreter.
+pub struct Vm {
+    path: PathBuf,
+    /// File of the module being run, empty when it isn't from a file
+    file: PathBuf,
+    modules: RefModules,
+    globals: RefGlobals,
+    exports: Vec<String>,
+    stack: Vec<Value>,
+    refs: Vec<RefVal>,
+    iterators: Vec<vec::IntoIter<(Value, Value)>>,
+    frames: Vec<Frame>,
+    handlers: Vec<Handler>,
+}
+
+impl Vm {
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_modules(path, RefModules::default())
+    }
+
+    /// Creates a machine sharing the imported modules of another one.
+    pub fn with_modules(path: PathBuf, modules: RefModules) -> Self {
+        Self {
+            path,
+            file: PathBuf::new(),
+            modules,
+            globals: RefGlobals::default(),
+            exports: Vec::new(),
+            stack: Vec::new(),
+            refs: Vec::new(),
+            iterators: Vec::new(),
+            frames: Vec::new(),
+            handlers: Vec::new(),
+        }
+    }
+
+    pub fn load_builtin(&mut self, builtins: Vec<Builtin>) {
+        for builtin in builtins {
+            let value = Rc::new(RefCell::new(Value::Builtin(builtin.clone())));
+            self.globals
+                .borrow_mut()
+                .insert(builtin.name.clone(), value);
+        }
+    }
+
+    /// Registers the file being run so that importing it back is reported as a
+    /// cycle.
+    pub fn set_main_module(&mut self, file_path: &Path) {
+        self.set_file(file_path);
+
+        if let Ok(path) = file_path.canonicalize() {
+            let _ = self.modules.borrow_mut().enter(path);
+        }
+    }
+
+    /// Sets the file of the module being run, errors and tracebacks refer to
+    /// it.
+    pub fn set_file(&mut self, file_path: &Path) {
+        self.file = file_path.to_path_buf();
+    }
+
+    pub fn interpret(&mut self, nodes: &[Statement]) -> Result<Value, RuntimeError> {
+        let prototype = Compiler::with_file(self.file.clone())
+            .compile(nodes)
+            .map_err(|error| error.in_file(&self.file))?;
+        self.exports.extend(prototype.exports.iter().cloned());
+
+        self.run(prototype)
+    }
+
+    /// Returns an object holding the current value of every exported global.
+    pub fn get_exports(&self) -> Value {
+        let values = self
+            .exports
+            .iter()
+            .filter_map(|name| {
+                let value = self.globals.borrow().get(name)?.borrow().clone();
+                Some((name.clone(), Rc::new(RefCell::new(value))))
+            })
+            .collect();
+
+        Value::new_object(values)
+    }
+
+    pub fn run_source(&mut self, source: &str) -> Result<Value, RuntimeError> {
+        let tokens = match Lexer::new(source).tokenize() {
+            Ok(value) => value,
+            Err(error) => {
+                let kind = RuntimeErrorKind::LexicalError(error.kind);
+                return Err(RuntimeError::new(kind, error.position).in_file(&self.file));
+            }
+        };
+        let nodes = match Parser::new(&tokens).parse() {
+            Ok(value) => value,
+            Err(error) => {
+                let kind = RuntimeErrorKind::ParsingError(error.kind);
+                return Err(RuntimeError::new(kind, error.position).in_file(&self.file));
+            }
+        };
+
+        self.interpret(&nodes)?;
+
+        Ok(self.get_exports())
+    }
+
+    pub fn run(&mut self, prototype: Prototype) -> Result<Value, RuntimeError> {
+        let closure = Rc::new(Closure {
+            prototype: Rc::new(prototype),
+            upvalues: Vec::new(),
+            globals: self.globals.clone(),
+        });
+
+        self.stack.clear();
+        self.refs.clear();
+        self.iterators.clear();
+        self.frames.clear();
+        self.handlers.clear();
+        self.frames.push(Frame::new(closure, 0, 0, 0));
+
+        let result = self.execute(0);
+
+        if result.is_err() {
+            self.frames.clear();
+        }
+
+        result
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap()
+    }
+
+    fn pop_ref(&mut self) -> RefVal {
+        self.refs.pop().unwrap()
+    }
+
+    fn get_global(
+        closure: &Closure,
+        name: &str,
+        position: Position,
+    ) -> Result<RefVal, RuntimeError> {
+        match closure.globals.borrow().get(name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(RuntimeError::new(
+                RuntimeErrorKind::UndefinedIdentifier(name.to_owned()),
+                position,
+            )),
+        }
+    }
+
+    /// Runs until the frames above `depth` return, errors are only caught by
+    /// the handlers of those frames.
+    fn execute(&mut self, depth: usize) -> Result<Value, RuntimeError> {
+        loop {
+            match self.dispatch(depth) {
+                Err(error) => match self.handlers.last() {
+                    Some(handler) if handler.frames > depth => {
+                        let handler = self.handlers.pop().unwrap();
+                        self.recover(handler, error);
+                    }
+                    _ => {
+                        let file = &self.frames.last().unwrap().closure.prototype.file;
+                        let error = self.unwind(error.in_file(file), depth);
+                        self.frames.truncate(depth);
+                        return Err(error);
+                    }
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Records the compiled functions above `depth` active when an uncaught
+    /// error was raised, the native calls are recorded by `call`.
+    fn unwind(&self, mut error: RuntimeError, depth: usize) -> RuntimeError {
+        let frames = &self.frames[depth.saturating_sub(1)..];
+
+        for (caller, callee) in frames.iter().zip(frames.iter().skip(1)).rev() {
+            let name = callee
+                .closure
+                .prototype
+                .name
+                .as_deref()
+                .unwrap_or("anonymous");
+            // the instruction pointer of the caller is past the call instruction
+            let position = caller.closure.prototype.chunk.positions[caller.ip - 1];
+            let path = caller.closure.prototype.file.clone();
+            error = error.with_frame(CallFrame::new(name, position, path));
+        }
+
+        error
+    }
+
+    /// Unwinds to the state saved by the handler and jumps to the `catch`
+    /// block with the error on top of the stack.
+    fn recover(&mut self, handler: Handler, error: RuntimeError) {
+        self.frames.truncate(handler.frames);
+        self.stack.truncate(handler.stack);
+        self.refs.truncate(handler.refs);
+        self.iterators.truncate(handler.iterators);
+
+        let frame = self.frame();
+        frame.loops.truncate(handler.loops);
+        frame.ip = handler.target;
+
+        self.stack.push(error.into_value());
+    }
+
+    fn dispatch(&mut self, depth: usize) -> Result<Value, RuntimeError> {
+        let mut closure = self.frame().closure.clone();
+
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+            let chunk = &closure.prototype.chunk;
+            let instruction = chunk.code[frame.ip];
+            let position = chunk.positions[frame.ip];
+            frame.ip += 1;
+
+            match instruction {
+                Instruction::Constant(index) => {
+                    let value = chunk.constants[index].clone();
+                    self.stack.push(value);
+                }
+                Instruction::Null => self.stack.push(Value::Null),
+                Instruction::Pop => {
+                    self.pop();
+                }
+                Instruction::Dup => {
+                    let value = self.stack.last().unwrap().clone();
+                    self.stack.push(value);
+                }
+                Instruction::DefineGlobal(index) => {
+                    let name = &chunk.names[index];
+
+                    if closure.globals.borrow().contains_key(name) {
+                        return Err(RuntimeError::new(
+                            RuntimeErrorKind::RedeclaringIdentifier(name.clone()),
+                            position,
+                        ));
+                    }
+
+                    let value = self.pop();
+                    closure
+                        .globals
+                        .borrow_mut()
+                        .insert(name.clone(), Rc::new(RefCell::new(value)));
+                }
+                Instruction::GetGlobal(index) => {
+                    let value = Self::get_global(&closure, &chunk.names[index], position)?;
+                    self.stack.push(value.borrow().clone());
+                }
+                Instruction::DefineLocal(slot) => {
+                    let value = self.pop();
+                    self.frame().slots[slot] = Rc::new(RefCell::new(value));
+                }
+                Instruction::GetLocal(slot) => {
+                    let value = self.frame().slots[slot].borrow().clone();
+                    self.stack.push(value);
+                }
+                Instruction::SetLocal(slot) => {
+                    let value = self.pop();
+                    *self.frame().slots[slot].borrow_mut() = value;
+                }
+                Instruction::GetUpvalue(index) => {
+                    let value = closure.upvalues[index].borrow().clone();
+                    self.stack.push(value);
+                }
+                Instruction::RefGlobal(index) => {
+                    let rf = Self::get_global(&closure, &chunk.names[index], position)?;
+                    self.refs.push(rf);
+                }
+                Instruction::RefLocal(slot) => {
+                    let rf = self.frame().slots[slot].clone();
+                    self.refs.push(rf);
+                }
+                Instruction::RefUpvalue(index) => {
+                    self.refs.push(closure.upvalues[index].clone());
+                }
+                Instruction::RefIndex => {
+                    let index = self.pop();
+                    let container = self.pop_ref();
+                    let rf = get_index_ref(&container.borrow(), index, position)?;
+                    self.refs.push(rf);
+                }
+                Instruction::RefProperty(index) => {
+                    let container = self.pop_ref();
+                    let prop = &chunk.names[index];
+                    let rf = get_property_ref(&container.borrow(), prop, position)?;
+                    self.refs.push(rf);
+                }
+                Instruction::Store(operator) => {
+                    let target = self.pop_ref();
+                    let value = self.pop();
+                    let result = assign_operation(&target, &operator.to_token(), value, position)?;
+                    self.stack.push(result);
+                }
+                Instruction::Unary(operator) => {
+                    let operand = self.pop();
+                    let value = unary_operation(&operator.to_token(), operand, position)?;
+                    self.stack.push(value);
+                }
+                Instruction::Binary(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let value = binary_operation(&operator.to_token(), left, right, position)?;
+                    self.stack.push(value);
+                }
+                Instruction::Array(len) => {
+                    let items = self
+                        .stack
+                        .split_off(self.stack.len() - len)
+                        .into_iter()
+                        .map(|value| Rc::new(RefCell::new(value)))
+                        .collect();
+                    self.stack.push(Value::new_array(items));
+                }
+                Instruction::Concat(len) => {
+                    let value = self
+                        .stack
+                        .split_off(self.stack.len() - len)
+                        .iter()
+                        .map(Value::to_string)
+                        .collect();
+                    self.stack.push(Value::String(value));
+                }
+                Instruction::Object(len) => {
+                    let props = self.stack.split_off(self.stack.len() - len * 2);
+                    let mut values = HashMap::new();
+                    let mut props = props.into_iter();
+
+                    while let (Some(Value::String(key)), Some(value)) = (props.next(), props.next())
+                    {
+                        values.insert(key, Rc::new(RefCell::new(value)));
+                    }
+
+                    self.stack.push(Value::new_object(values));
+                }
+                Instruction::GetIndex => {
+                    let index = self.pop();
+                    let value = self.pop();
+                    self.stack.push(get_index(value, index, position)?);
+                }
+                Instruction::GetProperty(index) => {
+                    let value = self.pop();
+                    let value = get_property(value, &chunk.names[index], position)?;
+                    self.stack.push(value);
+                }
+                Instruction::GetMember(index) => {
+                    let value = self.pop();
+                    let name = &chunk.names[index];
+                    let value = get_member(value, name, &self.modules, position)?;
+                    self.stack.push(value);
+                }
+                Instruction::Closure(index) => {
+                    let prototype = chunk.functions[index].clone();
+                    let frame = self.frames.last().unwrap();
+                    let upvalues = prototype
+                        .upvalues
+                        .iter()
+                        .map(|capture| match capture {
+                            Capture::Local(slot) => frame.slots[*slot].clone(),
+                            Capture::Upvalue(index) => closure.upvalues[*index].clone(),
+                        })
+                        .collect();
+                    let function = Compiled {
+                        name: prototype.name.clone(),
+                        inner: Rc::new(Closure {
+                            prototype,
+                            upvalues,
+                            globals: closure.globals.clone(),
+                        }),
+                    };
+                    self.stack
+                        .push(Value::Function(Function::Compiled(function)));
+                }
+                Instruction::Call(argc) => {
+                    let base = self.stack.len() - argc - 1;
+                    self.call(base, None, position)?;
+                    closure = self.frame().closure.clone();
+                }
+                Instruction::CallMethod(argc) => {
+                    let base = self.stack.len() - argc - 2;
+                    let this = self.stack.remove(base);
+                    self.call(base, Some(this), position)?;
+                    closure = self.frame().closure.clone();
+                }
+                Instruction::Return => {
+                    let value = self.pop();
+                    let frame = self.frames.pop().unwrap();
+                    let frames = self.frames.len();
+
+                    while matches!(self.handlers.last(), Some(handler) if handler.frames > frames) {
+                        self.handlers.pop();
+                    }
+
+                    self.stack.truncate(frame.stack_base);
+                    self.refs.truncate(frame.refs_base);
+                    self.iterators.truncate(frame.iterators_base);
+
+                    if frames == depth {
+                        return Ok(value);
+                    }
+
+                    self.stack.push(value);
+                    closure = self.frame().closure.clone();
+                }
+                Instruction::Jump(target) => frame.ip = target,
+                Instruction::JumpIfFalse(target) => {
+                    let condition = self.pop();
+
+                    if !is_truthy(&condition) {
+                        self.frame().ip = target;
+                    }
+                }
+                Instruction::EnterLoop => {
+                    let height = self.stack.len();
+                    let iterators = self.iterators.len();
+                    frame.loops.push((height, self.handlers.len(), iterators));
+                }
+                Instruction::ExitLoop => {
+                    frame.loops.pop();
+                }
+                Instruction::Unwind(exited) => {
+                    frame.loops.truncate(frame.loops.len() - exited);
+                    let (height, handlers, iterators) = *frame.loops.last().unwrap();
+                    self.stack.truncate(height);
+                    self.handlers.truncate(handlers);
+                    self.iterators.truncate(iterators);
+                }
+                Instruction::Iterate => {
+                    let value = self.pop();
+
+                    if !value.is_iterable() {
+                        return Err(RuntimeError::new(RuntimeErrorKind::NonIterable, position));
+                    }
+
+                    let items: Vec<_> = value.iter().collect();
+                    self.iterators.push(items.into_iter());
+                }
+                Instruction::Next(exit, keyed) => match self.iterators.last_mut().unwrap().next() {
+                    Some((key, value)) => {
+                        if keyed {
+                            self.stack.push(key);
+                        }
+                        self.stack.push(value);
+                    }
+                    None => self.frame().ip = exit,
+                },
+                Instruction::EndIterate => {
+                    self.iterators.pop();
+                }
+                Instruction::EnterTry(target) => {
+                    let loops = frame.loops.len();
+                    let handler = Handler {
+                        target,
+                        frames: self.frames.len(),
+                        stack: self.stack.len(),
+                        refs: self.refs.len(),
+                        iterators: self.iterators.len(),
+                        loops,
+                    };
+                    self.handlers.push(handler);
+                }
+                Instruction::ExitTry => {
+                    self.handlers.pop();
+                }
+            }
+        }
+    }
+
+    /// Calls the value at `base` with the arguments above it. Compiled
+    /// functions get a new frame while the other kinds are run to completion
+    /// and replaced by their result.
+    fn call(
+        &mut self,
+        base: usize,
+        this: Option<Value>,
+        position: Position,
+    ) -> Result<(), RuntimeError> {
+        let arguments = self.stack.split_off(base + 1);
+        let callee = self.pop();
+
+        match callee {
+            Value::Function(Function::Compiled(function)) => {
+                let closure = match function.inner.downcast::<Closure>() {
+                    Ok(closure) => closure,
+                    Err(_) => {
+                        return Err(RuntimeError::new(RuntimeErrorKind::NotFunciton, position))
+                    }
+                };
+                let expected = closure.prototype.arity;
+
+                if expected != arguments.len() {
+                    return Err(RuntimeError::new(
+                        RuntimeErrorKind::InvalidArgument(expected, arguments.len()),
+                        position,
+                    ));
+                }
+
+                let frame = Frame::new(closure, base, self.refs.len(), self.iterators.len());
+                *frame.slots[0].borrow_mut() = this.unwrap_or(Value::Null);
+
+                for (index, argument) in arguments.into_iter().enumerate() {
+                    *frame.slots[index + 1].borrow_mut() = argument;
+                }
+
+                self.frames.push(frame);
+            }
+            Value::Function(function) => {
+                let file = self.frame().closure.prototype.file.clone();
+                let value =
+                    call_function(&function, this, arguments, position).map_err(|error| {
+                        error.with_frame(CallFrame::new(function.get_name(), position, file))
+                    })?;
+                self.stack.push(value);
+            }
+            Value::Builtin(builtin) => {
+                let path = self.path.clone();
+                let file = self.frame().closure.prototype.file.clone();
+                let modules = self.modules.clone();
+                let caller = Callback(RefCell::new(&mut *self));
+                let context = CallContext::new(position, path, file.clone(), modules, &caller);
+                let value = builtin.call(&context, &arguments).map_err(|error| {
+                    error.with_frame(CallFrame::new(&builtin.name, position, file))
+                })?;
+                self.stack.push(value);
+            }
+            _ => return Err(RuntimeError::new(RuntimeErrorKind::NotFunciton, position)),
+        }
+
+        Ok(())
+    }
+
+    /// Calls a function for a native function and runs it to completion.
+    fn call_back(
+        &mut self,
+        function: &Value,
+        arguments: Vec<Value>,
+        position: Position,
+    ) -> Result<Value, RuntimeError> {
+        let base = self.stack.len();
+        let depth = self.frames.len();
+        self.stack.push(function.clone());
+        self.stack.extend(arguments);
+        self.call(base, None, position)?;
+
+        match self.frames.len() == depth {
+            true => Ok(self.pop()),
+            false => self.execute(depth),
+        }
+    }
+}
+
+/// Lets native functions call back into the machine running them.
+struct Callback<'a>(RefCell<&'a mut Vm>);
+
+impl Caller for Callback<'_> {
+    fn call(
+        &self,
+        context: &CallContext,
+        function: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        self.0
+            .borrow_mut()
+            .call_back(function, arguments, context.position)
+    }
+
+    fn run_module(
+        &self,
+        context: &CallContext,
+        file_path: &Path,
+        source: &str,
+    ) -> Result<Value, RuntimeError> {
+        let path = file_path.parent().unwrap().to_path_buf();
+        let mut vm = Vm::with_modules(path, context.get_modules());
+        vm.set_file(file_path);
+        vm.load_builtin(get_std_builtins());
+        vm.load_builtin(get_io_builtins());
+        vm.run_source(source)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Vm;
+    use interpreter::{
+        builtin::{get_io_builtins, get_std_builtins},
+        error::RuntimeErrorKind,
+        value::{Function, Value},
+        Interpreter,
+    };
+    use lexer::Lexer;
+    use parser::Parser;
+    use std::{env, fs, path::PathBuf};
+
+    fn run(source: &str) -> Vm {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let mut vm = Vm::new(PathBuf::new());
+        vm.load_builtin(get_std_builtins());
+        vm.interpret(&ast).unwrap();
+        vm
+    }
+
+    fn get(vm: &Vm, name: &str) -> Value {
+        vm.globals.borrow().get(name).unwrap().borrow().clone()
+    }
+
+    fn compare_backends(source: &str, names: &[&str]) {
+        let vm = run(source);
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        interpreter.interpret_program(&ast).unwrap();
+
+        for name in names {
+            let tokens = Lexer::new(name).tokenize().unwrap();
+            let node = Parser::new(&tokens).parse().unwrap().remove(0);
+            let expected = interpreter.interpret(node).unwrap().unwrap();
+            assert_eq!(get(&vm, name), expected, "mismatch for '{name}'");
+        }
+    }
+
+    #[test]
+    fn test_operations() {
+        let vm = run("
+            set a = 2;
+            set b = 4;
+            set c = (a + b) % 2 == 0;
+            b = a * b;
+            b -= 1;
+        ");
+
+        assert_eq!(get(&vm, "a"), Value::Number(2.0));
+        assert_eq!(get(&vm, "b"), Value::Number(7.0));
+        assert_eq!(get(&vm, "c"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_scopes() {
+        let vm = run("
+            set a = 2;
+            {
+                a = 3;
+                {
+                    set a = 4;
+                }
+                set b = false;
+            }
+        ");
+
+        assert_eq!(get(&vm, "a"), Value::Number(3.0));
+        assert!(!vm.globals.borrow().contains_key("b"));
+    }
+
+    #[test]
+    fn test_loops() {
+        let vm = run("
+            set i = 0;
+            while true {
+                i = i + 1;
+                if i == 3 {
+                    continue;
+                }
+                if (i % 3) == 0 {
+                    break;
+                }
+            }
+
+            set j = 0;
+            loop {
+                if j == 5 {
+                    break;
+                }
+                j += 1;
+            }
+
+            set factorial = 1;
+            for n in 1 to 6 {
+                factorial *= n;
+            }
+
+            set found = null;
+            for key, value in ['a', 'b', 'c'] {
+                if value == 'b' {
+                    found = key;
+                    break;
+                }
+            }
+
+            set pairs = '';
+            outer: for i in 0 to 3 {
+                inner: for j in 1 to 3 {
+                    while true {
+                        if j > i { continue outer }
+                        if i + j == 4 { break outer }
+                        break
+                    }
+                    pairs += '{i}{j} ';
+                }
+            }
+            set letters = '';
+            for letter in ['x', 'y'] {
+                letters += letter;
+            }
+        ");
+
+        assert_eq!(get(&vm, "i"), Value::Number(6.0));
+        assert_eq!(get(&vm, "j"), Value::Number(5.0));
+        assert_eq!(get(&vm, "factorial"), Value::Number(120.0));
+        assert_eq!(get(&vm, "found"), Value::Number(1.0));
+        assert_eq!(get(&vm, "pairs"), Value::String("11 21 ".to_owned()));
+        assert_eq!(get(&vm, "letters"), Value::String("xy".to_owned()));
+    }
+
+    #[test]
+    fn test_top_level_return() {
+        let tokens = Lexer::new("set a = 1\nif a { return a + 1 }\nset b = 2")
+            .tokenize()
+            .unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let mut vm = Vm::new(PathBuf::new());
+
+        assert_eq!(vm.interpret(&ast).unwrap(), Value::Number(2.0));
+        assert!(!vm.globals.borrow().contains_key("b"));
+    }
+
+    #[test]
+    fn test_functions() {
+        let vm = run("
+            function countdown(n) {
+                if n > 0 {
+                    return countdown(n - 1);
+                }
+                return n;
+            }
+            set a = countdown(5);
+
+            function counter() {
+                set count = 0;
+                lambda() {
+                    count += 1;
+                    count
+                }
+            }
+            set next = counter();
+            next();
+            set b = next();
+
+            function make_fact() {
+                set fact = lambda(n) if n <= 1 { 1 } else { n * fact(n - 1) };
+                fact
+            }
+            set c = make_fact()(5);
+
+            set callbacks = [];
+            for i in 3 {
+                callbacks[i] = lambda() i;
+            }
+            set d = callbacks[1]();
+
+            function outer() {
+                function inner(n) {
+                    if n == 0 { 'done' } else { inner(n - 1) }
+                }
+                while true {
+                    return inner(3);
+                }
+            }
+            set e = outer();
+        ");
+
+        assert_eq!(get(&vm, "a"), Value::Number(0.0));
+        assert_eq!(get(&vm, "b"), Value::Number(2.0));
+        assert_eq!(get(&vm, "c"), Value::Number(120.0));
+        assert_eq!(get(&vm, "d"), Value::Number(1.0));
+        assert_eq!(get(&vm, "e"), Value::String("done".to_owned()));
+    }
+
+    #[test]
+    fn test_objects() {
+        let vm = run("
+            set me = {
+                name: 'Luckas',
+                salute: lambda(other) 'Hello ' + other.name + ' I\\'m ' + self.name
+            };
+            set other = { name: 'stranger' };
+            set salute = me.salute(other);
+            me.happy = true;
+            me['age'] = 17;
+            set a = [1, 2, [0, 1]];
+            a[2][2] = 2;
+            a[4] = 3;
+        ");
+
+        assert_eq!(
+            get(&vm, "salute"),
+            Value::String("Hello stranger I'm Luckas".to_owned())
+        );
+        assert!(get(&vm, "me").to_string().contains("happy: true"));
+        assert_eq!(get(&vm, "a").to_string(), "[1, 2, [0, 1, 2], null, 3]");
+    }
+
+    #[test]
+    fn test_try() {
+        let vm = run("
+            function divide(a, b) {
+                if b == 0 {
+                    throw({ kind: 'custom' });
+                }
+                a / b
+            }
+            set a = null;
+            try {
+                divide(1, 0);
+            } catch (err) {
+                a = err.kind;
+            }
+
+            function safe_index(array, index) {
+                try {
+                    array[index]
+                } catch (err) {
+                    err.kind
+                }
+            }
+            set b = safe_index([1, 2], -1);
+
+            set c = 0;
+            for i in 5 {
+                try {
+                    if i == 2 { continue; }
+                    if i == 4 { break; }
+                    c += 1;
+                } catch (err) {}
+            }
+            try {
+                set d = [1, 2, 3];
+                for n in d {
+                    n / 0;
+                }
+            } catch (err) {
+                c = c + err.line;
+            }
+        ");
+
+        assert_eq!(get(&vm, "a"), Value::String("custom".to_owned()));
+        assert_eq!(get(&vm, "b"), Value::String("InvalidIndex".to_owned()));
+        assert_eq!(get(&vm, "c"), Value::Number(38.0));
+    }
+
+    #[test]
+    fn test_short_circuit() {
+        compare_backends(
+            "
+            set calls = 0;
+            set count = lambda() {
+                calls += 1;
+                true
+            };
+            set a = null or 'default';
+            set b = 0 and count();
+            set c = 'value' or count();
+            set d = null and null.next;
+            set e = 1 and 2;
+            set f = false or null;
+            set g = count() and 'counted';
+            ",
+            &["a", "b", "c", "d", "e", "f", "g", "calls"],
+        );
+    }
+
+    #[test]
+    fn test_hoisting() {
+        let vm = run("
+            set a = add(1, 2);
+
+            function add(a, b) {
+                a + b
+            }
+
+            function is_even(n) {
+                if n == 0 { true } else { is_odd(n - 1) }
+            }
+
+            function is_odd(n) {
+                if n == 0 { false } else { is_even(n - 1) }
+            }
+
+            function compute() {
+                set value = twice(3);
+
+                function twice(n) {
+                    double(n) * 2
+                }
+
+                function double(n) {
+                    n * 2
+                }
+
+                value
+            }
+            set b = compute();
+            set c = is_even(10);
+            set d = is_odd(7);
+        ");
+
+        assert_eq!(get(&vm, "a"), Value::Number(3.0));
+        assert_eq!(get(&vm, "b"), Value::Number(12.0));
+        assert_eq!(get(&vm, "c"), Value::Boolean(true));
+        assert_eq!(get(&vm, "d"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_exports() {
+        let vm = run("
+            export set sides = 4;
+            export function area(size) { size * size }
+            set hidden = true;
+            sides += 1;
+            set { a, b } = { a: 1, b: 2 };
+            function local() {
+                set { a } = { a: 3 };
+                a
+            }
+            set c = local();
+        ");
+
+        assert_eq!(get(&vm, "a"), Value::Number(1.0));
+        assert_eq!(get(&vm, "b"), Value::Number(2.0));
+        assert_eq!(get(&vm, "c"), Value::Number(3.0));
+
+        let Value::Object(exports) = vm.get_exports() else {
+            panic!("exports should be an object");
+        };
+        let exports = exports.borrow();
+        let mut keys: Vec<_> = exports.values.keys().collect();
+        keys.sort();
+
+        assert_eq!(keys, ["area", "sides"]);
+        assert_eq!(*exports.values["sides"].borrow(), Value::Number(5.0));
+    }
+
+    #[test]
+    fn test_import() {
+        let dir = env::temp_dir().join("icelang_test_vm_import");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("counter.ic"),
+            "set count = 0; export function next() { count += 1; count }",
+        )
+        .unwrap();
+
+        let mut vm = Vm::new(dir);
+        vm.load_builtin(get_std_builtins());
+        vm.load_builtin(get_io_builtins());
+        vm.run_source(
+            "
+            set { next } = import('counter');
+            next();
+            set a = next();
+            set b = import('counter').next == next;
+        ",
+        )
+        .unwrap();
+
+        assert_eq!(get(&vm, "a"), Value::Number(2.0));
+        assert_eq!(get(&vm, "b"), Value::Boolean(true));
+        assert!(matches!(
+            get(&vm, "next"),
+            Value::Function(Function::Compiled(_))
+        ));
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let tokens = Lexer::new("{ set a = 1; set a = 2 }").tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let error = Vm::new(PathBuf::new()).interpret(&ast).unwrap_err();
+
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::RedeclaringIdentifier("a".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_same_as_interpreter() {
+        compare_backends(
+            "
+            set a = match 3 {
+                1: false,
+                6, 3: { true },
+            };
+            set b = if (a and 2 > 1) { 'yes' } else { 'no' };
+            set c = 0;
+            for ch in 'a' to 'e' {
+                c += 1;
+            }
+            set d = type_of(lambda() null) + length([1, 2]);
+            set e = [floor(2.5), sqrt(16), 10 - 4 - 3];
+            set f = 'e is {e}, {a or b}{null} \\{}';
+            set g = [7 / 2, 6 / 3, -7 % 3, int(2.5), 9223372036854775807 + 1];
+            set h = [0.1m + 0.2m, 2n * 3, 1m / 4, 1n + 0.5, 0.10m];
+            set i = { list: [1] };
+            set j = i.list;
+            function grow(items) {
+                items[length(items)] = length(items) + 1;
+            }
+            grow(j);
+            set k = deep_clone(i);
+            k.list[0] = 0;
+            ",
+            &["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"],
+        );
+    }
+
+    #[test]
+    fn test_callbacks() {
+        compare_backends(
+            "
+            set a = [3, 1, 2];
+            sort_by(a, lambda(x, y) y - x);
+            set calls = 0;
+            function by_length(x, y) {
+                calls += 1;
+                length(x) - length(y)
+            }
+            set b = ['ccc', 'a', 'bb', 'dd'];
+            sort_by(b, by_length);
+            set c = null;
+            try {
+                sort_by([2, 1], lambda(x, y) throw('stop'));
+            } catch (err) {
+                c = err;
+            }
+            set d = [0];
+            try {
+                try {
+                    sort_by([2, 1], lambda(x, y) x / 0);
+                } catch (err) {
+                    d[0] = 1;
+                }
+            } catch (err) {
+                d[0] = 2;
+            }
+            set e = [2, 1];
+            sort_by(e, lambda(x, y) {
+                try {
+                    throw('ignored');
+                } catch (err) {}
+                x - y
+            });
+            ",
+            &["a", "b", "calls", "c", "d", "e"],
+        );
+
+        let vm = run("
+            set a = [3, 1, 2];
+            sort_by(a, lambda(x, y) y - x);
+            set b = ['ccc', 'a', 'bb', 'dd'];
+            sort_by(b, lambda(x, y) length(x) - length(y));
+        ");
+
+        assert_eq!(get(&vm, "a").to_string(), "[3, 2, 1]");
+        assert_eq!(get(&vm, "b").to_string(), "[a, bb, dd, ccc]");
+
+        let source = "
+function compare(x, y) {
+    x - null
+}
+sort_by([1, 2], compare)
+";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let mut vm = Vm::new(PathBuf::new());
+        vm.load_builtin(get_std_builtins());
+        let error = vm.interpret(&ast).unwrap_err();
+
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.load_builtin(get_std_builtins());
+        let expected = interpreter.interpret_program(&ast).unwrap_err();
+
+        assert_eq!(error.frames.len(), 2);
+        assert_eq!(error.frames, expected.frames);
+    }
+
+    #[test]
+    fn test_traceback() {
+        let source = "
+function inner(x) {
+    return sqrt(x)
+}
+set outer = lambda(x) {
+    inner(x)
+}
+outer('b')
+";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let ast = Parser::new(&tokens).parse().unwrap();
+        let path = PathBuf::from("main.ic");
+        let mut vm = Vm::new(PathBuf::new());
+        vm.set_file(&path);
+        vm.load_builtin(get_std_builtins());
+        let error = vm.interpret(&ast).unwrap_err();
+
+        let interpreter = Interpreter::new(PathBuf::new());
+        interpreter.set_file(&path);
+        interpreter.load_builtin(get_std_builtins());
+        let expected = interpreter.interpret_program(&ast).unwrap_err();
+
+        assert_eq!(error.frames.len(), 3);
+        assert_eq!(error.frames, expected.frames);
+        assert_eq!(error.path, Some(path));
+
+        let dir = env::temp_dir().join("icelang_test_vm_traceback");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("numbers.ic"),
+            "function check(x) {\n    1 / x\n}\nexport set divide = lambda(x) check(x);",
+        )
+        .unwrap();
+
+        let source = "set { divide } = import('numbers');\ndivide(0);";
+        let main = dir.join("main.ic");
+        let mut vm = Vm::new(dir.clone());
+        vm.set_file(&main);
+        vm.load_builtin(get_io_builtins());
+        let error = vm.run_source(source).unwrap_err();
+
+        let interpreter = Interpreter::new(dir.clone());
+        interpreter.set_file(&main);
+        interpreter.load_builtin(get_io_builtins());
+        let expected = interpreter.run_source(source).unwrap_err();
+
+        assert_eq!(
+            error.path,
+            Some(dir.join("numbers.ic").canonicalize().unwrap())
+        );
+        assert_eq!(error.path, expected.path);
+        assert_eq!(error.frames, expected.frames);
+    }
+}
@@ -1,20 +1,17 @@
-use std::f64::INFINITY;
-
 use interpreter::{
-    builtin::Builtin, environment::RefEnv, error::RuntimeError, value::Value, EvalExpr,
+    builtin::{Builtin, CallContext},
+    error::RuntimeError,
+    value::Value,
 };
-use lexer::tokens::Token;
-use parser::ast::Expression;
 
 use crate::print_to_output;
 
 pub fn get_io_builtins() -> Vec<Builtin> {
-    vec![Builtin::new("print", INFINITY as usize, io_print)]
+    vec![Builtin::new("print", f64::INFINITY as usize, io_print)]
 }
 
-fn io_print(env: &RefEnv, _: &Token, args: &[Expression]) -> Result<Value, RuntimeError> {
-    for arg in args {
-        let value = arg.evaluate_expression(env)?;
+fn io_print(_: &CallContext, args: &[Value]) -> Result<Value, RuntimeError> {
+    for value in args {
         print_to_output(&value.to_string())
     }
 
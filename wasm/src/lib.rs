@@ -23,18 +23,22 @@ pub fn interprete(source: &str) -> String {
         Ok(value) => value,
         Err(error) => return format!("Parsing error: {}", error),
     };
-    let ast = match Parser::new(&tokens).parse() {
-        Ok(value) => value,
-        Err(error) => return format!("Syntax error: {}", error),
-    };
+    let (ast, errors) = Parser::new(&tokens).parse_with_recovery();
+
+    if !errors.is_empty() {
+        return errors
+            .iter()
+            .map(|error| format!("Syntax error: {}", error))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
     let interpreter = Interpreter::new(PathBuf::new());
     interpreter.load_builtin(get_std_builtins());
     interpreter.load_builtin(get_io_builtins());
 
-    for node in ast {
-        if let Some(error) = interpreter.interpret(node).err() {
-            return format!("Runtime error: {}", error);
-        };
+    if let Some(error) = interpreter.interpret_program(&ast).err() {
+        return format!("Runtime error: {}", error);
     }
 
     String::new()
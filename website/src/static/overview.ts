@@ -15,8 +15,10 @@ export const section = [
     header: "Data types",
     paragraph: "Icelang provides basic data types and is dynamically typed.",
     code: `set string = "Hello World\\n"; -- supports escape characters
-set integer = 123;
+set integer = 123; -- 64 bits, overflows to a float
 set float = 1.2;
+set bigint = 123n; -- integer of any size, divisions round toward zero
+set decimal = 0.10m; -- exact, 0.1m + 0.2m == 0.3m
 set bool = true;
 set range = 0 to 5; -- non inclusive 
 set array = [1, 2, 3, 4];
@@ -42,7 +44,8 @@ set object = {
 variable = "value"
 array[0] = "value" -- array indexing
 object.prop = "value" -- object literal indexing
-object[variable] = "value" -- dynamic object indexing`,
+object[variable] = "value" -- dynamic object indexing
+set alias = array -- arrays and objects are shared, clone(array) or deep_clone(array) copy them`,
   },
   {
     header: "Conditionals",
@@ -130,8 +133,12 @@ print("Hello World");
 set input = readline();
 
 -- Utility
-type_of(a);
+type_of(a); -- "number" for integers and floats
+is_number(1m); -- true for every number kind
+is_integer(2.0); -- false, only integers and big integers
 parse_number("1");
+int(2.9); -- 2, truncated toward zero
+float(2);
 length("hello");
 
 -- Math
@@ -139,6 +146,20 @@ sqrt(8);
 pow(2, 5);
 floor(2.5);
 round(2.5);
-ceil(2.5);`,
+ceil(2.5);
+
+-- Arrays, modified in place
+push(array, 5);
+pop(array);
+insert(array, 0, "first");
+remove(array, 0);
+reverse(array);
+sort(array);
+sort_by(array, lambda(a, b) b - a); -- negative when a goes first
+-- changing the array from the comparison is an error
+slice(array, 1, 3); -- new array, end excluded
+concat(array, [6, 7]);
+index_of(array, 3); -- -1 when missing
+contains(array, 3);`,
   },
 ];